@@ -0,0 +1,674 @@
+//! # INSTAFollows Ultimate - Headless CLI
+//!
+//! Same Ghost Client as the desktop app, without a webview: load a session,
+//! scan into snapshot files, diff and export them, or import a data archive.
+//! Scans and imports are also recorded in the local store.
+
+use anyhow::{anyhow, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use instafollows_core::analysis::{diff, fans, mutuals, relationships, Category};
+use instafollows_core::archive::import_archive;
+use instafollows_core::budget::{BudgetExceeded, RequestLedger, ANONYMOUS_ACCOUNT};
+use instafollows_core::endpoints::{EndpointConfig, ENDPOINTS_FILE};
+use instafollows_core::filters::Filter;
+use instafollows_core::logging::{export_logs, init_logging};
+use instafollows_core::progress::{ProgressEvent, ProgressSink, WaitReason};
+use instafollows_core::settings::{Settings, SharedSettings, SETTINGS_FILE};
+use instafollows_core::stats::{follow_durations, growth_stats, quick_unfollowers, GrowthStats, QuickUnfollowOptions};
+use instafollows_core::storage::{read_rule_sets, RULES_FILE, SESSION_FILE};
+use instafollows_core::store::{ProfileField, Store, DATABASE_FILE};
+use instafollows_core::{GhostClient, Profile, Snapshot};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Diagnostics log directory, next to the other working files
+const LOG_DIR: &str = "logs";
+
+/// Set by `--record`: every client started by this run records to it
+static RECORD_TO: OnceLock<PathBuf> = OnceLock::new();
+
+// ============================================
+// ARGUMENTS
+// ============================================
+
+#[derive(Parser)]
+#[command(name = "instafollows", version, about = "Headless INSTAFollows Ultimate")]
+struct Cli {
+    /// Print JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Record every request and response, secrets redacted, to this file
+    #[arg(long, global = true)]
+    record: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage the saved session
+    #[command(subcommand)]
+    Session(SessionCommand),
+
+    /// Scan an account and save the result as a snapshot
+    Scan {
+        /// Account to scan (defaults to the logged-in user)
+        #[arg(short, long)]
+        username: Option<String>,
+        /// Snapshot file to write
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+        /// Pacing profile: gentle, standard, overnight or a custom one
+        #[arg(long)]
+        pacing: Option<String>,
+    },
+
+    /// Compare two snapshots of the same account
+    Diff { old: PathBuf, new: PathBuf },
+
+    /// Show every relationship category of a snapshot
+    Relationships {
+        snapshot: PathBuf,
+        /// Earlier snapshot to report changes against
+        #[arg(short, long)]
+        previous: Option<PathBuf>,
+        /// Only list these categories (repeatable, defaults to all)
+        #[arg(short, long, value_enum)]
+        category: Vec<CategoryArg>,
+        #[command(flatten)]
+        rules: RuleArgs,
+    },
+
+    /// Export one list from a snapshot
+    Export {
+        snapshot: PathBuf,
+        #[arg(short, long, value_enum, default_value_t = ListKind::Traitors)]
+        list: ListKind,
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+        #[command(flatten)]
+        rules: RuleArgs,
+    },
+
+    /// Growth and churn of an account across the scans in the local store
+    Stats {
+        user_id: String,
+        /// Only scans taken at or after this Unix timestamp
+        #[arg(long)]
+        since: Option<u64>,
+    },
+
+    /// How long followers have stayed, and how quickly those who left did
+    Durations { user_id: String },
+
+    /// Accounts that unfollowed soon after following, or keep cycling
+    QuickUnfollowers {
+        user_id: String,
+        /// Longest stay that still counts as a quick unfollow
+        #[arg(long, default_value_t = 7.0)]
+        window_days: f64,
+        /// Departures that count as repeated follow/unfollow
+        #[arg(long, default_value_t = 2)]
+        min_cycles: usize,
+    },
+
+    /// Requests used and left in every budget window
+    Budget {
+        /// Account to report on (defaults to the saved session's)
+        #[arg(long)]
+        account: Option<String>,
+    },
+
+    /// Check whether a scan fits in the remaining request budget
+    Preflight {
+        /// Account to check (defaults to the logged-in user)
+        #[arg(short, long)]
+        username: Option<String>,
+        /// Pacing profile the scan would use
+        #[arg(long)]
+        pacing: Option<String>,
+    },
+
+    /// Show the network settings, or reset them to the defaults
+    Settings {
+        #[arg(long)]
+        reset: bool,
+    },
+
+    /// Write the diagnostics log, secrets scrubbed, to a file for a bug report
+    ExportLogs { out: PathBuf },
+
+    /// Show the recorded username and display name changes of a profile
+    History { profile_id: String },
+
+    /// Import an extracted "Download your information" archive as a snapshot
+    ImportArchive {
+        path: PathBuf,
+        /// ID of the archived account, if known
+        #[arg(long)]
+        user_id: Option<String>,
+        /// Snapshot file to write
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Filter output through a saved or shared rule set
+#[derive(Args)]
+struct RuleArgs {
+    /// Rule set file (defaults to the saved rule sets)
+    #[arg(long)]
+    rules: Option<PathBuf>,
+    /// Rule set to apply (optional if the file holds exactly one)
+    #[arg(long)]
+    rule_set: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum SessionCommand {
+    /// Load cookies.json exported from the browser and save it for later runs
+    Load { path: PathBuf },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ListKind {
+    Followers,
+    Following,
+    Traitors,
+    Mutuals,
+    Fans,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CategoryArg {
+    Mutuals,
+    NotFollowingBack,
+    Fans,
+    Changed,
+}
+
+impl From<CategoryArg> for Category {
+    fn from(arg: CategoryArg) -> Self {
+        match arg {
+            CategoryArg::Mutuals => Category::Mutuals,
+            CategoryArg::NotFollowingBack => Category::NotFollowingBack,
+            CategoryArg::Fans => Category::Fans,
+            CategoryArg::Changed => Category::Changed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+// ============================================
+// ENTRY
+// ============================================
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    if let Err(e) = init_logging(LOG_DIR, Settings::load_or_default(SETTINGS_FILE).log_level()) {
+        eprintln!("Logging disabled: {}", e);
+    }
+    if let Some(path) = cli.record.clone() {
+        let _ = RECORD_TO.set(path);
+    }
+
+    match cli.command {
+        Command::Session(SessionCommand::Load { path }) => {
+            let mut client = new_client()?;
+            let message = client.load_session(&path.to_string_lossy())?;
+            if cli.json {
+                print_json(&serde_json::json!({ "message": message, "user_id": client.user_id }))?;
+            } else {
+                println!("{}", message);
+            }
+        }
+        Command::Scan { username, out, pacing } => scan(username, out, pacing, cli.json).await?,
+        Command::Diff { old, new } => {
+            let changes = diff(&Snapshot::load(&old)?, &Snapshot::load(&new)?);
+            if cli.json {
+                print_json(&changes)?;
+            } else {
+                print_section("New followers", &changes.new_followers);
+                print_section("Lost followers", &changes.lost_followers);
+                print_section("Started following", &changes.new_following);
+                print_section("Stopped following", &changes.dropped_following);
+            }
+        }
+        Command::Relationships { snapshot, previous, category, rules } => {
+            let current = Snapshot::load(&snapshot)?;
+            let previous = previous.map(Snapshot::load).transpose()?;
+            let mut report = relationships(&current, previous.as_ref());
+            if !category.is_empty() {
+                let categories: Vec<Category> = category.into_iter().map(Category::from).collect();
+                report = report.only(&categories);
+            }
+            if let Some(filter) = load_filter(rules)? {
+                report = filter.apply_relationships(&report);
+            }
+
+            if cli.json {
+                print_json(&report)?;
+            } else {
+                let counts = &report.counts;
+                println!(
+                    "Followers: {}  Following: {}  Mutuals: {}  Not following back: {}  Fans: {}",
+                    counts.followers, counts.following, counts.mutuals, counts.not_following_back, counts.fans
+                );
+                print_section("Mutuals", &report.mutuals);
+                print_section("Not following back", &report.not_following_back);
+                print_section("Fans", &report.fans);
+                if let Some(changes) = &report.changes {
+                    print_section("New followers", &changes.new_followers);
+                    print_section("Lost followers", &changes.lost_followers);
+                    print_section("Started following", &changes.new_following);
+                    print_section("Stopped following", &changes.dropped_following);
+                }
+            }
+        }
+        Command::Export { snapshot, list, format, out, rules } => {
+            let snapshot = Snapshot::load(&snapshot)?;
+            let profiles = match list {
+                ListKind::Followers => snapshot.followers,
+                ListKind::Following => snapshot.following,
+                ListKind::Traitors => snapshot.traitors(),
+                ListKind::Mutuals => mutuals(&snapshot.followers, &snapshot.following),
+                ListKind::Fans => fans(&snapshot.followers, &snapshot.following),
+            };
+            let profiles = match load_filter(rules)? {
+                Some(filter) => filter.apply(&profiles),
+                None => profiles,
+            };
+            let data = match format {
+                ExportFormat::Csv => to_csv(&profiles),
+                ExportFormat::Json => serde_json::to_string_pretty(&profiles)?,
+            };
+            match out {
+                Some(path) => std::fs::write(&path, data)?,
+                None => std::io::stdout().write_all(data.as_bytes())?,
+            }
+        }
+        Command::Stats { user_id, since } => {
+            let stats = growth_stats(&Store::open(DATABASE_FILE)?.snapshots(&user_id, since)?);
+            if cli.json {
+                print_json(&stats)?;
+            } else {
+                print_stats(&stats);
+            }
+        }
+        Command::Durations { user_id } => {
+            let durations = follow_durations(&Store::open(DATABASE_FILE)?.snapshots(&user_id, None)?);
+            if cli.json {
+                print_json(&durations)?;
+            } else {
+                println!("Current followers ({})", durations.current.len());
+                for tenure in &durations.current {
+                    let at_least = if tenure.predates_history { ">=" } else { "" };
+                    println!("{:<32}  {}{:.1} days", tenure.profile.username, at_least, tenure.days);
+                }
+                println!("\nLeft ({})", durations.departed.len());
+                for departed in &durations.departed {
+                    println!(
+                        "{:<32}  {:.1} days  ({} - {})",
+                        departed.profile.username, departed.days, departed.first_seen, departed.last_seen
+                    );
+                }
+            }
+        }
+        Command::QuickUnfollowers { user_id, window_days, min_cycles } => {
+            let snapshots = Store::open(DATABASE_FILE)?.snapshots(&user_id, None)?;
+            let cyclers = quick_unfollowers(&snapshots, &QuickUnfollowOptions { window_days, min_cycles });
+            if cli.json {
+                print_json(&cyclers)?;
+            } else {
+                println!("Quick or repeated unfollowers ({})", cyclers.len());
+                for cycler in &cyclers {
+                    let stays: Vec<String> = cycler.stays.iter().map(|s| format!("{:.1}d", s.days)).collect();
+                    let mut tags = Vec::new();
+                    if cycler.quick {
+                        tags.push("quick");
+                    }
+                    if cycler.repeated {
+                        tags.push("repeated");
+                    }
+                    if cycler.following_now {
+                        tags.push("following now");
+                    }
+                    println!("{:<32}  stays: {:<24}  {}", cycler.profile.username, stays.join(", "), tags.join(", "));
+                }
+            }
+        }
+        Command::Budget { account } => {
+            let account = match account {
+                Some(account) => account,
+                None => {
+                    let mut client = new_client()?;
+                    let _ = client.restore_session();
+                    client.user_id.unwrap_or_else(|| ANONYMOUS_ACCOUNT.to_string())
+                }
+            };
+            let report = RequestLedger::open(DATABASE_FILE)?.report(&account)?;
+            if cli.json {
+                print_json(&report)?;
+            } else {
+                println!("Account {}  meter {}%", report.account_id, report.meter);
+                for usage in &report.usage {
+                    println!(
+                        "{:<7} {:>5} / {:<5} per {}m",
+                        usage.kind.as_str(),
+                        usage.used,
+                        usage.limit,
+                        usage.window_secs / 60
+                    );
+                }
+            }
+        }
+        Command::Preflight { username, pacing } => {
+            let (client, user_id) = session_client(username).await?;
+            let check = client.preflight_scan(&user_id, pacing.as_deref()).await?;
+            if cli.json {
+                print_json(&check)?;
+            } else if check.fits {
+                println!(
+                    "Scan needs about {} requests, {} per read window, {} left - OK",
+                    check.estimate.requests, check.in_window, check.remaining
+                );
+            } else {
+                println!("{}", BudgetExceeded(check));
+            }
+        }
+        Command::Settings { reset } => {
+            let settings = if reset {
+                let settings = Settings::default();
+                settings.save(SETTINGS_FILE)?;
+                settings
+            } else if Path::new(SETTINGS_FILE).exists() {
+                Settings::load(SETTINGS_FILE)?
+            } else {
+                Settings::default()
+            };
+            if cli.json {
+                print_json(&settings)?;
+            } else {
+                println!("Request timeout  {} s", settings.request_timeout_secs);
+                println!("Connect timeout  {} s", settings.connect_timeout_secs);
+                println!("Delay            {}-{} ms", settings.delay_min_ms, settings.delay_max_ms);
+                println!("Page size        {}", settings.page_size);
+                match &settings.session_file {
+                    Some(path) => println!("Session file     {}", path.display()),
+                    None => println!("Session file     {} (default)", SESSION_FILE),
+                }
+                println!("Pacing           {}", settings.pacing);
+            }
+        }
+        Command::ExportLogs { out } => {
+            let lines = export_logs(LOG_DIR, &out)?;
+            println!("Wrote {} log lines to {}", lines, out.display());
+        }
+        Command::History { profile_id } => {
+            let store = Store::open(DATABASE_FILE)?;
+            let changes = store.profile_history(&profile_id)?;
+            if cli.json {
+                print_json(&changes)?;
+            } else if changes.is_empty() {
+                println!("No recorded changes for {}", profile_id);
+            } else {
+                if let Some(profile) = store.profile(&profile_id)? {
+                    println!("{} ({})", profile.username, profile.full_name);
+                }
+                for change in &changes {
+                    let field = match change.field {
+                        ProfileField::Username => "username",
+                        ProfileField::FullName => "full name",
+                    };
+                    println!("{}  {}: {} -> {}", change.detected_at, field, change.old_value, change.new_value);
+                }
+            }
+        }
+        Command::ImportArchive { path, user_id, out } => {
+            let snapshot = import_archive(&path, user_id.as_deref())?;
+            let out = out.unwrap_or_else(|| default_snapshot_path(&snapshot));
+            snapshot.save(&out)?;
+            Store::open(DATABASE_FILE)?.save_snapshot(&snapshot)?;
+            print_summary(&snapshot, &out, cli.json)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn new_client() -> Result<GhostClient> {
+    let settings = SharedSettings::new(Settings::load_or_default(SETTINGS_FILE));
+    let mut client = GhostClient::with_settings(EndpointConfig::load_or_default(ENDPOINTS_FILE), settings)?;
+    client.set_ledger(Arc::new(RequestLedger::open(DATABASE_FILE)?));
+    // The CLI keeps its data in the working directory, next to the store
+    client.set_default_session_file(std::env::current_dir()?.join(SESSION_FILE));
+    if let Some(path) = RECORD_TO.get() {
+        client.start_recording(path)?;
+    }
+    Ok(client)
+}
+
+fn load_filter(args: RuleArgs) -> Result<Option<Filter>> {
+    if args.rules.is_none() && args.rule_set.is_none() {
+        return Ok(None);
+    }
+
+    let path = args.rules.unwrap_or_else(|| PathBuf::from(RULES_FILE));
+    let sets = read_rule_sets(&path)?;
+    let set = match &args.rule_set {
+        Some(name) => sets
+            .iter()
+            .find(|s| &s.name == name)
+            .ok_or_else(|| anyhow!("No rule set named {:?} in {}", name, path.display()))?,
+        None if sets.len() == 1 => &sets[0],
+        None => return Err(anyhow!("{} holds {} rule sets, pick one with --rule-set", path.display(), sets.len())),
+    };
+
+    Ok(Some(set.compile()?))
+}
+
+/// Client with the saved session, and the ID of the account to work on
+async fn session_client(username: Option<String>) -> Result<(GhostClient, String)> {
+    let mut client = new_client()?;
+    client
+        .restore_session()
+        .map_err(|e| anyhow!("{} - run `instafollows session load <cookies.json>` first", e))?;
+    client.warmup().await?;
+
+    let user_id = match username {
+        Some(username) => client.get_user_id(&username).await?,
+        None => client
+            .user_id
+            .clone()
+            .ok_or_else(|| anyhow!("Session has no user ID, pass --username"))?,
+    };
+    Ok((client, user_id))
+}
+
+async fn scan(username: Option<String>, out: Option<PathBuf>, pacing: Option<String>, json: bool) -> Result<()> {
+    let (client, user_id) = session_client(username).await?;
+    let snapshot = client.fetch_snapshot(&user_id, pacing.as_deref(), &TerminalSink).await?;
+
+    let out = out.unwrap_or_else(|| default_snapshot_path(&snapshot));
+    snapshot.save(&out)?;
+    Store::open(DATABASE_FILE)?.save_snapshot(&snapshot)?;
+    print_summary(&snapshot, &out, json)
+}
+
+// ============================================
+// OUTPUT
+// ============================================
+
+/// Scan progress on stderr, so stdout stays clean for --json
+struct TerminalSink;
+
+impl ProgressSink for TerminalSink {
+    fn report(&self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::Page { stage, page, current, total, eta_ms } => {
+                let eta = eta_ms.map(|ms| format!("  ~{}s left", ms / 1000)).unwrap_or_default();
+                eprint!("\r{:<10} page {:>4}  {:>6} / {:<6}{}   ", stage, page, current, total, eta);
+            }
+            ProgressEvent::Retry { stage, page, attempt, reason } => {
+                eprintln!("\n{} page {}: retry {} ({})", stage, page, attempt, reason);
+            }
+            ProgressEvent::Wait { stage, duration_ms, reason: WaitReason::Break } => {
+                eprintln!("\n{}: taking a {}s break", stage, duration_ms / 1000);
+            }
+            ProgressEvent::Wait { .. } => {}
+            ProgressEvent::Done { stage, total } => eprintln!("\r{:<10} done: {:<40}", stage, total),
+        }
+    }
+}
+
+fn default_snapshot_path(snapshot: &Snapshot) -> PathBuf {
+    let owner = if snapshot.user_id.is_empty() { "archive" } else { &snapshot.user_id };
+    PathBuf::from(format!("snapshot-{}-{}.json", owner, snapshot.taken_at))
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_summary(snapshot: &Snapshot, path: &Path, json: bool) -> Result<()> {
+    let traitors = snapshot.traitors();
+    if json {
+        return print_json(&serde_json::json!({
+            "snapshot": path,
+            "total_followers": snapshot.followers.len(),
+            "total_following": snapshot.following.len(),
+            "traitors": traitors,
+        }));
+    }
+
+    println!("Snapshot saved to {}", path.display());
+    println!(
+        "Followers: {}  Following: {}  Not following back: {}",
+        snapshot.followers.len(),
+        snapshot.following.len(),
+        traitors.len()
+    );
+    print_section("Not following back", &traitors);
+    Ok(())
+}
+
+fn print_stats(stats: &GrowthStats) {
+    println!(
+        "{:<12}  {:>9}  {:>9}  {:>7}  {:>6}  {:>6}  {:>6}",
+        "TAKEN AT", "FOLLOWERS", "FOLLOWING", "MUTUAL%", "GAINED", "LOST", "CHURN%"
+    );
+    let optional = |n: Option<usize>| n.map(|n| n.to_string()).unwrap_or_else(|| "-".into());
+    for point in &stats.points {
+        println!(
+            "{:<12}  {:>9}  {:>9}  {:>7.1}  {:>6}  {:>6}  {:>6}",
+            point.taken_at,
+            point.followers,
+            point.following,
+            point.mutual_ratio * 100.0,
+            optional(point.gained_followers),
+            optional(point.lost_followers),
+            point.churn_rate.map(|r| format!("{:.1}", r * 100.0)).unwrap_or_else(|| "-".into()),
+        );
+    }
+    if let Some(days) = stats.average_follow_days {
+        println!("\nAverage follow duration: {:.1} days", days);
+    }
+}
+
+fn print_section(title: &str, profiles: &[Profile]) {
+    println!("\n{} ({})", title, profiles.len());
+    if !profiles.is_empty() {
+        print_table(profiles);
+    }
+}
+
+fn print_table(profiles: &[Profile]) {
+    let headers = ["USERNAME", "FULL NAME", "ID", "FLAGS"];
+    let rows: Vec<[String; 4]> = profiles
+        .iter()
+        .map(|p| [p.username.clone(), p.full_name.clone(), p.id.clone(), flags(p)])
+        .collect();
+
+    let mut widths = headers.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let print_row = |cells: [&str; 4]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+
+    print_row(headers);
+    for row in &rows {
+        print_row([&row[0], &row[1], &row[2], &row[3]]);
+    }
+}
+
+/// V = verified, P = private, B = business/creator
+fn flags(p: &Profile) -> String {
+    let mut flags = String::new();
+    if p.is_verified {
+        flags.push('V');
+    }
+    if p.is_private {
+        flags.push('P');
+    }
+    if p.is_business_account || p.is_professional_account {
+        flags.push('B');
+    }
+    flags
+}
+
+fn to_csv(profiles: &[Profile]) -> String {
+    fn field(value: &str) -> String {
+        if value.contains([',', '"', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+
+    let mut csv = String::from(
+        "id,username,full_name,is_verified,is_private,is_business_account,is_professional_account,category_name,profile_pic_url\n",
+    );
+    for p in profiles {
+        let row = [
+            field(&p.id),
+            field(&p.username),
+            field(&p.full_name),
+            p.is_verified.to_string(),
+            p.is_private.to_string(),
+            p.is_business_account.to_string(),
+            p.is_professional_account.to_string(),
+            field(p.category_name.as_deref().unwrap_or("")),
+            field(&p.profile_pic_url),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
@@ -0,0 +1,194 @@
+//! # INSTAFollows Ultimate - Analysis
+//!
+//! Set algebra over follower/following lists: relationship categories and
+//! snapshot diffs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::models::{Profile, Snapshot, SnapshotDiff};
+
+// ============================================
+// DATA STRUCTURES
+// ============================================
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// Follow each other
+    Mutuals,
+    /// Followed, but don't follow back
+    NotFollowingBack,
+    /// Follow you, not followed back
+    Fans,
+    /// Changed since the previous snapshot
+    Changed,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RelationshipCounts {
+    pub followers: usize,
+    pub following: usize,
+    pub mutuals: usize,
+    pub not_following_back: usize,
+    pub fans: usize,
+    /// Total changes since the previous snapshot, `None` without one
+    pub changed: Option<usize>,
+}
+
+/// Every relationship category for one snapshot
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Relationships {
+    pub mutuals: Vec<Profile>,
+    pub not_following_back: Vec<Profile>,
+    pub fans: Vec<Profile>,
+    /// Changes since the previous snapshot, `None` without one
+    pub changes: Option<SnapshotDiff>,
+    /// Always reflect the full analysis, even after `only`
+    pub counts: RelationshipCounts,
+}
+
+// ============================================
+// PROFILE MATCHING
+// ============================================
+
+/// Profiles are matched by ID when every list has them, otherwise by
+/// username (archive imports only carry usernames)
+pub(crate) fn keyed_by_id(lists: &[&[Profile]]) -> bool {
+    lists.iter().all(|list| list.iter().all(|p| !p.id.is_empty()))
+}
+
+pub(crate) fn profile_key(profile: &Profile, keyed_by_id: bool) -> &str {
+    if keyed_by_id {
+        &profile.id
+    } else {
+        &profile.username
+    }
+}
+
+/// Entries of `b` missing from `a`, each listed once
+fn missing_from(a: &[Profile], b: &[Profile], keyed_by_id: bool) -> Vec<Profile> {
+    let keys: HashSet<&str> = a.iter().map(|p| profile_key(p, keyed_by_id)).collect();
+    let mut seen = HashSet::new();
+    b.iter()
+        .filter(|p| {
+            let key = profile_key(p, keyed_by_id);
+            !keys.contains(key) && seen.insert(key)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Entries of `b` also present in `a`, each listed once
+fn present_in(a: &[Profile], b: &[Profile], keyed_by_id: bool) -> Vec<Profile> {
+    let keys: HashSet<&str> = a.iter().map(|p| profile_key(p, keyed_by_id)).collect();
+    let mut seen = HashSet::new();
+    b.iter()
+        .filter(|p| {
+            let key = profile_key(p, keyed_by_id);
+            keys.contains(key) && seen.insert(key)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Distinct profiles in a list
+pub(crate) fn unique_count(profiles: &[Profile], keyed_by_id: bool) -> usize {
+    profiles.iter().map(|p| profile_key(p, keyed_by_id)).collect::<HashSet<_>>().len()
+}
+
+// ============================================
+// RELATIONSHIP CATEGORIES
+// ============================================
+
+/// People followed who don't follow back
+pub fn non_mutuals(followers: &[Profile], following: &[Profile]) -> Vec<Profile> {
+    missing_from(followers, following, keyed_by_id(&[followers, following]))
+}
+
+/// People who follow and are followed back
+pub fn mutuals(followers: &[Profile], following: &[Profile]) -> Vec<Profile> {
+    present_in(followers, following, keyed_by_id(&[followers, following]))
+}
+
+/// People who follow but aren't followed back
+pub fn fans(followers: &[Profile], following: &[Profile]) -> Vec<Profile> {
+    missing_from(following, followers, keyed_by_id(&[followers, following]))
+}
+
+/// Split a snapshot into every category, with changes against `previous`
+pub fn relationships(current: &Snapshot, previous: Option<&Snapshot>) -> Relationships {
+    let mutuals = mutuals(&current.followers, &current.following);
+    let not_following_back = non_mutuals(&current.followers, &current.following);
+    let fans = fans(&current.followers, &current.following);
+    let changes = previous.map(|previous| diff(previous, current));
+    let keyed_by_id = keyed_by_id(&[&current.followers, &current.following]);
+
+    let counts = RelationshipCounts {
+        followers: unique_count(&current.followers, keyed_by_id),
+        following: unique_count(&current.following, keyed_by_id),
+        mutuals: mutuals.len(),
+        not_following_back: not_following_back.len(),
+        fans: fans.len(),
+        changed: changes.as_ref().map(|c| {
+            c.new_followers.len() + c.lost_followers.len() + c.new_following.len() + c.dropped_following.len()
+        }),
+    };
+
+    Relationships {
+        mutuals,
+        not_following_back,
+        fans,
+        changes,
+        counts,
+    }
+}
+
+impl Relationships {
+    /// Keep only the requested categories' lists (counts are untouched)
+    pub fn only(mut self, categories: &[Category]) -> Self {
+        if !categories.contains(&Category::Mutuals) {
+            self.mutuals.clear();
+        }
+        if !categories.contains(&Category::NotFollowingBack) {
+            self.not_following_back.clear();
+        }
+        if !categories.contains(&Category::Fans) {
+            self.fans.clear();
+        }
+        if !categories.contains(&Category::Changed) {
+            self.changes = None;
+        }
+        self
+    }
+}
+
+impl Snapshot {
+    /// People followed who don't follow back
+    pub fn traitors(&self) -> Vec<Profile> {
+        non_mutuals(&self.followers, &self.following)
+    }
+}
+
+// ============================================
+// DIFF
+// ============================================
+
+pub fn diff(old: &Snapshot, new: &Snapshot) -> SnapshotDiff {
+    let keyed_by_id = keyed_by_id(&[&old.followers, &old.following, &new.followers, &new.following]);
+
+    SnapshotDiff {
+        new_followers: missing_from(&old.followers, &new.followers, keyed_by_id),
+        lost_followers: missing_from(&new.followers, &old.followers, keyed_by_id),
+        new_following: missing_from(&old.following, &new.following, keyed_by_id),
+        dropped_following: missing_from(&new.following, &old.following, keyed_by_id),
+    }
+}
+
+/// Mutuals in `old` that are no longer mutual in `new` (either side left)
+pub fn lost_mutuals(old: &Snapshot, new: &Snapshot) -> Vec<Profile> {
+    let keyed_by_id = keyed_by_id(&[&old.followers, &old.following, &new.followers, &new.following]);
+    let before = present_in(&old.followers, &old.following, keyed_by_id);
+    let after = present_in(&new.followers, &new.following, keyed_by_id);
+    missing_from(&after, &before, keyed_by_id)
+}
@@ -0,0 +1,120 @@
+//! # INSTAFollows Ultimate - Data Archive Import
+//!
+//! Reads the follower/following lists from an extracted Instagram
+//! "Download your information" archive (JSON format) into a snapshot,
+//! without touching the network.
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::models::{unix_now, Profile, Snapshot, SnapshotSource};
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Where the lists live inside the archive, newest layout first
+const CONNECTIONS_DIRS: [&str; 2] = ["connections/followers_and_following", "followers_and_following"];
+
+const FOLLOWING_FILE: &str = "following.json";
+const FOLLOWING_KEY: &str = "relationships_following";
+
+// ============================================
+// IMPORT
+// ============================================
+
+/// Import an extracted archive folder. The archive carries no user IDs, so
+/// the scanned account's ID can be supplied separately.
+pub fn import_archive(root: &Path, user_id: Option<&str>) -> Result<Snapshot> {
+    let dir = find_connections_dir(root)?;
+
+    // Large accounts get followers_1.json, followers_2.json, ...
+    let mut follower_files: Vec<PathBuf> = std::fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with("followers_") && n.ends_with(".json"))
+                .unwrap_or(false)
+        })
+        .collect();
+    follower_files.sort();
+
+    if follower_files.is_empty() {
+        return Err(anyhow!("No followers_*.json found in {}", dir.display()));
+    }
+
+    let mut followers = Vec::new();
+    let mut latest = 0;
+    for path in &follower_files {
+        // Followers files are a bare array of entries
+        let json = read_json(path)?;
+        let entries = json
+            .as_array()
+            .ok_or_else(|| anyhow!("Unexpected format in {}", path.display()))?;
+        followers.extend(entries.iter().filter_map(|e| parse_entry(e, &mut latest)));
+    }
+
+    let following_path = dir.join(FOLLOWING_FILE);
+    let json = read_json(&following_path)?;
+    let entries = json[FOLLOWING_KEY]
+        .as_array()
+        .ok_or_else(|| anyhow!("Missing {} in {}", FOLLOWING_KEY, following_path.display()))?;
+    let following = entries.iter().filter_map(|e| parse_entry(e, &mut latest)).collect();
+
+    Ok(Snapshot {
+        user_id: user_id.unwrap_or("").to_string(),
+        // Most recent relationship timestamp approximates the export date
+        taken_at: if latest > 0 { latest } else { unix_now() },
+        source: SnapshotSource::Archive,
+        followers,
+        following,
+    })
+}
+
+fn find_connections_dir(root: &Path) -> Result<PathBuf> {
+    CONNECTIONS_DIRS
+        .iter()
+        .map(|d| root.join(d))
+        .chain(std::iter::once(root.to_path_buf()))
+        .find(|d| d.join(FOLLOWING_FILE).exists())
+        .ok_or_else(|| anyhow!("{} doesn't look like an extracted Instagram archive", root.display()))
+}
+
+fn read_json(path: &Path) -> Result<Value> {
+    let file = File::open(path).map_err(|e| anyhow!("Cannot open {}: {}", path.display(), e))?;
+    serde_json::from_reader(BufReader::new(file)).map_err(|e| anyhow!("Invalid JSON in {}: {}", path.display(), e))
+}
+
+/// Parse one relationship entry. Older exports put the username in
+/// `string_list_data[0].value`, newer ones in `title`.
+fn parse_entry(entry: &Value, latest: &mut u64) -> Option<Profile> {
+    let data = &entry["string_list_data"][0];
+
+    let username = data["value"]
+        .as_str()
+        .or_else(|| entry["title"].as_str())
+        .filter(|s| !s.is_empty())
+        .or_else(|| data["href"].as_str().and_then(|h| h.trim_end_matches('/').rsplit('/').next()))?
+        .to_string();
+
+    if let Some(ts) = data["timestamp"].as_u64() {
+        *latest = (*latest).max(ts);
+    }
+
+    Some(Profile {
+        id: String::new(),
+        username,
+        full_name: String::new(),
+        profile_pic_url: String::new(),
+        profile_pic_url_hd: None,
+        is_verified: false,
+        is_private: false,
+        is_business_account: false,
+        is_professional_account: false,
+        category_name: None,
+    })
+}
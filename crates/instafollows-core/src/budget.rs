@@ -0,0 +1,329 @@
+//! # INSTAFollows Ultimate - Request Budget
+//!
+//! Persisted, per-account sliding-window ledger of the requests actually
+//! sent, by category, checked against configurable budgets. The stealth meter
+//! is the remaining share of the tightest budget, and scans are checked
+//! against what's left before they start.
+
+use anyhow::{anyhow, Result};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::models::unix_now;
+use crate::pacing::Pace;
+use crate::store::Store;
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Store setting holding the `BudgetConfig`
+pub const BUDGETS_SETTING: &str = "request_budgets";
+
+/// Ledger account for requests sent before a session is loaded
+pub const ANONYMOUS_ACCOUNT: &str = "anonymous";
+
+/// Ledger entries older than this are pruned on open
+const LEDGER_RETENTION_SECS: u64 = 7 * 86_400;
+
+const HOUR_SECS: u64 = 3600;
+
+// ============================================
+// BUDGETS
+// ============================================
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestKind {
+    /// Follower/following pages and page loads
+    Read,
+    /// Profile lookups (username -> ID, current user)
+    Lookup,
+    /// Unfollows
+    Write,
+}
+
+impl RequestKind {
+    pub const ALL: [RequestKind; 3] = [RequestKind::Read, RequestKind::Lookup, RequestKind::Write];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestKind::Read => "read",
+            RequestKind::Lookup => "lookup",
+            RequestKind::Write => "write",
+        }
+    }
+}
+
+/// At most `limit` requests in any `window_secs`
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub limit: u32,
+    pub window_secs: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct BudgetConfig {
+    #[serde(default = "default_read")]
+    pub read: Budget,
+    #[serde(default = "default_lookup")]
+    pub lookup: Budget,
+    #[serde(default = "default_write")]
+    pub write: Budget,
+}
+
+fn default_read() -> Budget {
+    Budget {
+        limit: 300,
+        window_secs: HOUR_SECS,
+    }
+}
+
+fn default_lookup() -> Budget {
+    Budget {
+        limit: 100,
+        window_secs: HOUR_SECS,
+    }
+}
+
+fn default_write() -> Budget {
+    Budget {
+        limit: 100,
+        window_secs: 24 * HOUR_SECS,
+    }
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        BudgetConfig {
+            read: default_read(),
+            lookup: default_lookup(),
+            write: default_write(),
+        }
+    }
+}
+
+impl BudgetConfig {
+    pub fn budget(&self, kind: RequestKind) -> Budget {
+        match kind {
+            RequestKind::Read => self.read,
+            RequestKind::Lookup => self.lookup,
+            RequestKind::Write => self.write,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for kind in RequestKind::ALL {
+            let budget = self.budget(kind);
+            if budget.limit == 0 {
+                return Err(anyhow!("{} budget needs a limit above 0", kind.as_str()));
+            }
+            if !(60..=LEDGER_RETENTION_SECS).contains(&budget.window_secs) {
+                return Err(anyhow!("{} budget window must be between 1 minute and 7 days", kind.as_str()));
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================
+// USAGE REPORT
+// ============================================
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BudgetUsage {
+    pub kind: RequestKind,
+    pub used: u32,
+    pub limit: u32,
+    pub window_secs: u64,
+    pub remaining: u32,
+    /// Unix time the oldest counted request leaves the window
+    pub frees_at: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BudgetReport {
+    pub account_id: String,
+    pub usage: Vec<BudgetUsage>,
+    /// 0-100: remaining share of the tightest budget
+    pub meter: u8,
+}
+
+impl BudgetReport {
+    pub fn usage(&self, kind: RequestKind) -> Option<&BudgetUsage> {
+        self.usage.iter().find(|u| u.kind == kind)
+    }
+}
+
+// ============================================
+// SCAN PRE-FLIGHT
+// ============================================
+
+/// Read requests a scan will send, from the account's connection counts
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct ScanEstimate {
+    pub followers: u64,
+    pub following: u64,
+    pub requests: u32,
+}
+
+impl ScanEstimate {
+    pub fn new(followers: u64, following: u64, page_size: usize) -> Self {
+        let pages = |count: u64| count.div_ceil(page_size.max(1) as u64).max(1);
+        ScanEstimate {
+            followers,
+            following,
+            requests: u32::try_from(pages(followers) + pages(following)).unwrap_or(u32::MAX),
+        }
+    }
+}
+
+/// Whether a scan fits in the read budget right now
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ScanPreflight {
+    pub account_id: String,
+    pub estimate: ScanEstimate,
+    /// Requests of the scan sent within one read window at its pacing
+    pub in_window: u32,
+    pub remaining: u32,
+    pub fits: bool,
+    /// When enough recent requests leave the window for the scan to fit.
+    /// `None` if it fits now, or never will at this pacing.
+    pub next_safe_at: Option<u64>,
+}
+
+/// A scan refused before it started
+#[derive(Debug)]
+pub struct BudgetExceeded(pub ScanPreflight);
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let check = &self.0;
+        write!(
+            f,
+            "Scan sends about {} requests per read window but only {} are left",
+            check.in_window, check.remaining
+        )?;
+        match check.next_safe_at.and_then(|at| Local.timestamp_opt(at as i64, 0).single()) {
+            Some(at) => write!(f, "; next safe window at {}", at.format("%Y-%m-%d %H:%M")),
+            None => write!(f, "; pick a slower pacing profile or raise the read budget"),
+        }
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+// ============================================
+// LEDGER
+// ============================================
+
+/// Shared by the client (recording) and the UI (reporting)
+pub struct RequestLedger {
+    store: Mutex<Store>,
+}
+
+impl RequestLedger {
+    /// Open the ledger in the local database and prune old entries
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_store(Store::open(path)?)
+    }
+
+    pub fn with_store(store: Store) -> Result<Self> {
+        store.prune_requests(unix_now().saturating_sub(LEDGER_RETENTION_SECS))?;
+        Ok(RequestLedger {
+            store: Mutex::new(store),
+        })
+    }
+
+    fn store(&self) -> std::sync::MutexGuard<'_, Store> {
+        // A panic mid-insert leaves nothing half-written worth refusing
+        self.store.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Count one request. Failures are logged: the ledger never blocks a request.
+    pub fn record(&self, account_id: &str, kind: RequestKind) {
+        if let Err(e) = self.store().record_request(account_id, kind, unix_now()) {
+            log::warn!("Request ledger: {}", e);
+        }
+    }
+
+    pub fn budgets(&self) -> Result<BudgetConfig> {
+        Ok(self.store().setting(BUDGETS_SETTING)?.unwrap_or_default())
+    }
+
+    pub fn set_budgets(&self, budgets: &BudgetConfig) -> Result<()> {
+        budgets.validate()?;
+        self.store().set_setting(BUDGETS_SETTING, budgets)
+    }
+
+    /// Usage of every budget over its current window
+    pub fn report(&self, account_id: &str) -> Result<BudgetReport> {
+        let budgets = self.budgets()?;
+        let store = self.store();
+        let now = unix_now();
+
+        let mut usage = Vec::with_capacity(RequestKind::ALL.len());
+        for kind in RequestKind::ALL {
+            let budget = budgets.budget(kind);
+            let (used, oldest) = store.requests_since(account_id, kind, now.saturating_sub(budget.window_secs))?;
+            usage.push(BudgetUsage {
+                kind,
+                used,
+                limit: budget.limit,
+                window_secs: budget.window_secs,
+                remaining: budget.limit.saturating_sub(used),
+                frees_at: oldest.map(|at| at + budget.window_secs),
+            });
+        }
+
+        let meter = usage
+            .iter()
+            .map(|u| (u64::from(u.remaining) * 100 / u64::from(u.limit.max(1))) as u8)
+            .min()
+            .unwrap_or(100);
+
+        Ok(BudgetReport {
+            account_id: account_id.to_string(),
+            usage,
+            meter,
+        })
+    }
+
+    /// Compare a scan paced by `reads` with the read budget left. Only the
+    /// requests sent within one window count: a slow scan spreads the rest
+    /// over later windows, once earlier requests have expired.
+    pub fn preflight(&self, account_id: &str, estimate: ScanEstimate, reads: &Pace) -> Result<ScanPreflight> {
+        let budget = self.budgets()?.read;
+        let now = unix_now();
+        let times = self
+            .store()
+            .request_times(account_id, RequestKind::Read, now.saturating_sub(budget.window_secs))?;
+
+        let in_window = u32::try_from(reads.max_requests_in(budget.window_secs))
+            .unwrap_or(u32::MAX)
+            .min(estimate.requests);
+        let used = u32::try_from(times.len()).unwrap_or(u32::MAX);
+        let remaining = budget.limit.saturating_sub(used);
+        let fits = in_window <= remaining;
+
+        // The scan fits once the oldest `used + in_window - limit` requests expire
+        let next_safe_at = if fits || in_window > budget.limit {
+            None
+        } else {
+            let expiring = (used + in_window - budget.limit) as usize;
+            times.get(expiring - 1).map(|at| at + budget.window_secs)
+        };
+
+        Ok(ScanPreflight {
+            account_id: account_id.to_string(),
+            estimate,
+            in_window,
+            remaining,
+            fits,
+            next_safe_at,
+        })
+    }
+}
@@ -0,0 +1,798 @@
+//! # INSTAFollows Ultimate - Stealth Network Layer
+//! 
+//! The "Doppelgänger" Client: Uses rquest with Chrome133 TLS fingerprinting
+//! to bypass Instagram's Botguard detection.
+
+use anyhow::{anyhow, Result};
+use rquest::header::{HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, ORIGIN, REFERER, USER_AGENT};
+use rquest::{Client, Impersonate, Method, StatusCode};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use url::Url;
+
+use crate::analysis::non_mutuals;
+use crate::budget::{BudgetExceeded, RequestKind, RequestLedger, ScanEstimate, ScanPreflight, ANONYMOUS_ACCOUNT};
+use crate::endpoints::EndpointConfig;
+use crate::models::{unix_now, Profile, ScanResult, Snapshot, SnapshotSource};
+use crate::progress::{estimate_eta, ProgressEvent, ProgressSink, WaitReason};
+use crate::pacing::{pacing_profile, Pace, PacingProfile};
+use crate::recording::{HttpReply, Recorder};
+use crate::replay::Replayer;
+use crate::redact::{redact, redact_headers, CsrfToken, SessionId};
+use crate::settings::SharedSettings;
+use crate::storage::{read_cookies, write_cookies, SESSION_FILE};
+use crate::sources::{source_by_name, ConnectionSource, Direction, PageRequest, SchemaError};
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+// Query hashes, app IDs and paths live in `endpoints` (overridable at runtime)
+const API_DOMAIN: &str = "www.instagram.com";
+
+/// Retries per page on network errors, 429 and 5xx
+const MAX_RETRIES: u32 = 3;
+/// First retry backoff, doubled on every attempt
+const RETRY_BACKOFF: Duration = Duration::from_secs(5);
+
+// ============================================
+// GHOST CLIENT - Chrome133 Impersonation
+// ============================================
+
+/// Pacing state of one scan
+struct ScanPace {
+    profile: String,
+    /// Read requests sent so far
+    sent: AtomicU32,
+}
+
+/// Clones share the connection, cookies, ledger, unfollow count and
+/// recorder, so a long scan can run on a clone without holding the original
+#[derive(Clone)]
+pub struct GhostClient {
+    client: Client,
+    endpoints: EndpointConfig,
+    pub user_id: Option<String>,
+    pub username: Option<String>,
+    pub csrf_token: Option<CsrfToken>,
+    session_id: Option<SessionId>,
+    cookies_loaded: bool,
+    /// Counts every request sent, if attached
+    ledger: Option<Arc<RequestLedger>>,
+    /// Read on every request, so edits apply to running scans
+    settings: SharedSettings,
+    /// Session copy location unless the settings name one
+    default_session_file: PathBuf,
+    /// Unfollows sent since the session was loaded
+    writes_sent: Arc<AtomicU32>,
+    /// Debug mode: every exchange is written to a recording file
+    recorder: Option<Arc<Recorder>>,
+    /// Offline mode: responses come from a recording, waits are skipped
+    replay: Option<Arc<Replayer>>,
+}
+
+impl GhostClient {
+    pub fn new() -> Result<Self> {
+        Self::with_endpoints(EndpointConfig::default())
+    }
+
+    /// Build a client using the given endpoint definitions
+    pub fn with_endpoints(endpoints: EndpointConfig) -> Result<Self> {
+        Self::with_settings(endpoints, SharedSettings::default())
+    }
+
+    /// Build a client using the given endpoint definitions and settings
+    pub fn with_settings(endpoints: EndpointConfig, settings: SharedSettings) -> Result<Self> {
+        let current = settings.get();
+
+        // Build rquest client with Chrome133 TLS fingerprint
+        let client = Client::builder()
+            .impersonate(Impersonate::Chrome133)
+            .cookie_store(true)
+            .timeout(current.request_timeout())
+            .connect_timeout(current.connect_timeout())
+            .build()
+            .map_err(|e| anyhow!("Failed to build Ghost Client: {}", e))?;
+
+        Ok(GhostClient {
+            client,
+            endpoints,
+            user_id: None,
+            username: None,
+            csrf_token: None,
+            session_id: None,
+            cookies_loaded: false,
+            ledger: None,
+            settings,
+            default_session_file: PathBuf::from(SESSION_FILE),
+            writes_sent: Arc::new(AtomicU32::new(0)),
+            recorder: None,
+            replay: None,
+        })
+    }
+
+    fn get_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_static(
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/133.0.0.0 Safari/537.36",
+            ),
+        );
+        headers.insert(ACCEPT, HeaderValue::from_static("*/*"));
+        headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.9"));
+        if let Ok(val) = HeaderValue::from_str(&self.endpoints.web_app_id) {
+            headers.insert("X-IG-App-ID", val);
+        }
+        headers.insert("X-Requested-With", HeaderValue::from_static("XMLHttpRequest"));
+        if let Ok(val) = HeaderValue::from_str(&self.endpoints.asbd_id) {
+            headers.insert("X-ASBD-ID", val);
+        }
+        headers.insert(
+            ORIGIN,
+            HeaderValue::from_str(&format!("https://{}", API_DOMAIN)).unwrap(),
+        );
+        headers.insert(
+            REFERER,
+            HeaderValue::from_str(&format!("https://{}/", API_DOMAIN)).unwrap(),
+        );
+        headers.insert("Sec-Fetch-Dest", HeaderValue::from_static("empty"));
+        headers.insert("Sec-Fetch-Mode", HeaderValue::from_static("cors"));
+        headers.insert("Sec-Fetch-Site", HeaderValue::from_static("same-origin"));
+
+        if let Some(ref token) = self.csrf_token {
+            if let Ok(val) = HeaderValue::from_str(token.expose()) {
+                headers.insert("X-CSRFToken", val);
+            }
+        }
+
+        headers
+    }
+
+    /// Swap endpoint definitions (e.g. after reloading the override file)
+    pub fn set_endpoints(&mut self, endpoints: EndpointConfig) {
+        self.endpoints = endpoints;
+    }
+
+    /// Count requests in a ledger from now on
+    pub fn set_ledger(&mut self, ledger: Arc<RequestLedger>) {
+        self.ledger = Some(ledger);
+    }
+
+    /// The loaded session cookie; prints as `[redacted]`
+    pub fn session_id(&self) -> Option<&SessionId> {
+        self.session_id.as_ref()
+    }
+
+    /// Settings this client reads; `set` on it reaches running scans
+    pub fn settings(&self) -> &SharedSettings {
+        &self.settings
+    }
+
+    /// Built-in or custom pacing profile, the one in the settings for `None`
+    pub fn pacing_profile(&self, name: Option<&str>) -> Result<PacingProfile> {
+        let settings = self.settings.get();
+        match name {
+            Some(name) => pacing_profile(&settings, name),
+            None => Ok(settings.default_pacing()),
+        }
+    }
+
+    fn scan_pace(&self, pacing: Option<&str>) -> Result<ScanPace> {
+        Ok(ScanPace {
+            profile: self.pacing_profile(pacing)?.name,
+            sent: AtomicU32::new(0),
+        })
+    }
+
+    /// Looked up on every page so profile edits apply mid-scan
+    fn read_pace(&self, scan: &ScanPace) -> Pace {
+        let settings = self.settings.get();
+        pacing_profile(&settings, &scan.profile)
+            .unwrap_or_else(|_| settings.default_pacing())
+            .reads
+    }
+
+    fn request_timeout(&self) -> Duration {
+        self.settings.get().request_timeout()
+    }
+
+    /// Account requests are attributed to in the ledger
+    pub fn ledger_account(&self) -> &str {
+        self.user_id.as_deref().unwrap_or(ANONYMOUS_ACCOUNT)
+    }
+
+    fn record_request(&self, kind: RequestKind) {
+        if let Some(ledger) = &self.ledger {
+            ledger.record(self.ledger_account(), kind);
+        }
+    }
+
+    /// Keep the session copy at `path` unless the settings name a file
+    pub fn set_default_session_file(&mut self, path: impl Into<PathBuf>) {
+        self.default_session_file = path.into();
+    }
+
+    /// Where the copy of the last loaded cookies.json is kept
+    pub fn session_file(&self) -> PathBuf {
+        self.settings.get().session_file.unwrap_or_else(|| self.default_session_file.clone())
+    }
+
+    /// Record every request and response to `path` from now on
+    pub fn start_recording(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let recorder = Recorder::create(path)?;
+        log::info!("Recording HTTP traffic to {}", recorder.path().display());
+        self.recorder = Some(Arc::new(recorder));
+        Ok(())
+    }
+
+    /// Stop recording, returning the file written
+    pub fn stop_recording(&mut self) -> Option<PathBuf> {
+        self.recorder.take().map(|r| r.path().to_path_buf())
+    }
+
+    pub fn recording_path(&self) -> Option<&Path> {
+        self.recorder.as_ref().map(|r| r.path())
+    }
+
+    /// Serve every request from `replayer` instead of the network (`None`
+    /// goes back online)
+    pub fn set_replay(&mut self, replayer: Option<Arc<Replayer>>) {
+        self.replay = replayer;
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Wait between requests; instant when replaying
+    async fn pause(&self, duration: Duration) {
+        if self.replay.is_none() {
+            sleep(duration).await;
+        }
+    }
+
+    /// GET with the API headers
+    async fn get(&self, url: &str, query: &[(&str, &str)]) -> Result<HttpReply> {
+        self.send(Method::GET, url, query, self.get_headers(), &[]).await
+    }
+
+    /// Every request goes through here, so the recorder sees all of them
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        query: &[(&str, &str)],
+        headers: HeaderMap,
+        form: &[(&str, &str)],
+    ) -> Result<HttpReply> {
+        let url = if query.is_empty() {
+            Url::parse(url)?
+        } else {
+            Url::parse_with_params(url, query)?
+        };
+        log::debug!("{} {} [{}]", method, redact(url.as_str()), redact_headers(&headers));
+
+        let started = Instant::now();
+        if let Some(replayer) = &self.replay {
+            let result = replayer.reply(&method, &url);
+            if let Some(recorder) = &self.recorder {
+                recorder.record(&method, url.as_str(), &headers, form, &result, started.elapsed());
+            }
+            return result;
+        }
+
+        let mut builder = self
+            .client
+            .request(method.clone(), url.clone())
+            .timeout(self.request_timeout())
+            .headers(headers.clone());
+        if !form.is_empty() {
+            builder = builder.form(form);
+        }
+
+        let result = async {
+            let resp = builder.send().await?;
+            let status = resp.status();
+            let headers = resp.headers().clone();
+            let body = resp.bytes().await?.to_vec();
+            anyhow::Ok(HttpReply { status, headers, body })
+        }
+        .await;
+
+        if let Some(recorder) = &self.recorder {
+            recorder.record(&method, url.as_str(), &headers, form, &result, started.elapsed());
+        }
+        result
+    }
+
+    /// Build a full URL for an endpoint path
+    fn endpoint_url(&self, path: &str) -> String {
+        format!("https://{}{}", API_DOMAIN, path)
+    }
+
+    /// Load session from cookies.json exported from browser
+    pub fn load_session(&mut self, file_path: &str) -> Result<String> {
+        let cookies = read_cookies(file_path)?;
+
+        // INTERNAL PERSISTENCE: Save a copy to the app directory
+        // This allows restoring the session even if the original file is moved/deleted.
+        let _ = write_cookies(self.session_file(), &cookies);
+
+        let url = Url::parse(&format!("https://{}", API_DOMAIN))?;
+        let mut found_session = false;
+        let mut found_csrf = false;
+        
+        // Collect all cookies as HeaderValues
+        let mut cookie_headers: Vec<HeaderValue> = Vec::new();
+
+        for cookie in &cookies {
+            let cookie_str = format!(
+                "{}={}",
+                cookie.name,
+                cookie.value
+            );
+            if let Ok(header_val) = HeaderValue::from_str(&cookie_str) {
+                cookie_headers.push(header_val);
+            }
+
+            if cookie.name == "sessionid" {
+                self.session_id = Some(SessionId::new(cookie.value.clone()));
+                self.user_id = Some(cookie.value.split('%').next().unwrap_or("").to_string());
+                found_session = true;
+            }
+            if cookie.name == "csrftoken" {
+                self.csrf_token = Some(CsrfToken::new(cookie.value.clone()));
+                found_csrf = true;
+            }
+            if cookie.name == "ds_user" {
+                self.username = Some(cookie.value.clone());
+            }
+        }
+        
+        // Set all cookies at once
+        self.client.set_cookies(&url, &cookie_headers);
+
+        if found_session && found_csrf {
+            self.cookies_loaded = true;
+            self.writes_sent.store(0, Ordering::Relaxed);
+            Ok(format!(
+                "Session loaded. User ID: {}",
+                self.user_id.as_deref().unwrap_or("unknown")
+            ))
+        } else {
+            Err(anyhow!(
+                "Invalid cookies.json: Missing sessionid or csrftoken"
+            ))
+        }
+    }
+
+    /// Try to restore session from internal storage
+    pub fn restore_session(&mut self) -> Result<String> {
+        let session_file = self.session_file();
+        if session_file.exists() {
+            self.load_session(&session_file.to_string_lossy())
+        } else {
+             Err(anyhow!("No saved session found"))
+        }
+    }
+
+    /// Warmup connection to establish Keep-Alive
+    pub async fn warmup(&self) -> Result<()> {
+        let url = format!("https://{}/", API_DOMAIN);
+        self.record_request(RequestKind::Read);
+        let _ = self.get(&url, &[]).await;
+        Ok(())
+    }
+
+    /// Fetch user ID from username
+    pub async fn get_user_id(&self, username: &str) -> Result<String> {
+        let url = self.endpoint_url(&self.endpoints.paths.web_profile_info);
+        self.record_request(RequestKind::Lookup);
+
+        let json: Value = self.get(&url, &[("username", username)]).await?.json()?;
+
+        json["data"]["user"]["id"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| anyhow!("User not found: {}", username))
+    }
+
+    /// Fetch current logged-in user profile
+    pub async fn fetch_current_user(&self) -> Result<Profile> {
+        // 1. Try to get Username from cached cookie or Edit Account Data
+        let username = if let Some(ref u) = self.username {
+            u.clone()
+        } else {
+            let url = self.endpoint_url(&self.endpoints.paths.edit_web_form_data);
+            self.record_request(RequestKind::Lookup);
+            let json: Value = self.get(&url, &[]).await?.json()?;
+
+            json["form_data"]["username"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Failed to fetch current username"))?
+                .to_string()
+        };
+
+        // 2. Get full profile info using Web Profile Info
+        // We manually call the profile info endpoint to get the Profile struct
+        let info_url = self.endpoint_url(&self.endpoints.paths.web_profile_info);
+        self.record_request(RequestKind::Lookup);
+        let info_json: Value = self.get(&info_url, &[("username", &username)]).await?.json()?;
+        Ok(Profile::from_json(&info_json["data"]["user"]))
+    }
+
+    /// Fetch followers (GraphQL pagination, friendships API as fallback)
+    pub async fn fetch_followers(&self, user_id: &str, sink: &dyn ProgressSink) -> Result<Vec<Profile>> {
+        self.fetch_connections(user_id, Direction::Followers, &self.scan_pace(None)?, sink).await
+    }
+
+    /// Fetch following (GraphQL pagination, friendships API as fallback)
+    pub async fn fetch_following(&self, user_id: &str, sink: &dyn ProgressSink) -> Result<Vec<Profile>> {
+        self.fetch_connections(user_id, Direction::Following, &self.scan_pace(None)?, sink).await
+    }
+
+    /// Try each configured connection source in order, falling back to the
+    /// next one when a source returns a schema error
+    async fn fetch_connections(
+        &self,
+        user_id: &str,
+        direction: Direction,
+        scan: &ScanPace,
+        sink: &dyn ProgressSink,
+    ) -> Result<Vec<Profile>> {
+        let mut last_error = None;
+
+        for name in &self.endpoints.sources {
+            let Some(source) = source_by_name(name) else {
+                continue;
+            };
+
+            match self.fetch_from_source(source, user_id, direction, scan, sink).await {
+                Ok(profiles) => return Ok(profiles),
+                Err(e) if e.is::<SchemaError>() => {
+                    log::warn!("{} source failed for {}: {}", source.name(), direction.as_str(), e);
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| anyhow!("No usable connection source configured")))
+    }
+
+    /// Paginate through a single connection source with progress reporting
+    async fn fetch_from_source(
+        &self,
+        source: &dyn ConnectionSource,
+        user_id: &str,
+        direction: Direction,
+        scan: &ScanPace,
+        sink: &dyn ProgressSink,
+    ) -> Result<Vec<Profile>> {
+        let stage = direction.as_str();
+        let started = Instant::now();
+        let mut profiles = Vec::new();
+        let mut seen = HashSet::new();
+        let mut cursor: Option<String> = None;
+        let mut total_count = 0;
+        let mut page_number = 0;
+
+        loop {
+            page_number += 1;
+            let page_size = self.settings.get().page_size;
+            let request = source.page_request(&self.endpoints, user_id, direction, cursor.as_deref(), page_size);
+            let json = self.fetch_page(source, &request, stage, page_number, scan, sink).await?;
+            let page = source.parse_page(&json, direction)?;
+
+            // Get total count on first page
+            if total_count == 0 {
+                total_count = page.total.unwrap_or(0);
+            }
+
+            // The list can shift between pages and repeat an account
+            let (received, listed) = (page.profiles.len(), profiles.len());
+            profiles.extend(page.profiles.into_iter().filter(|p| p.id.is_empty() || seen.insert(p.id.clone())));
+            let repeated = received - (profiles.len() - listed);
+            if repeated > 0 {
+                log::warn!("{} page {}: skipped {} profiles already listed", stage, page_number, repeated);
+            }
+
+            // Report Progress
+            sink.report(ProgressEvent::Page {
+                stage,
+                page: page_number,
+                current: profiles.len(),
+                total: total_count,
+                eta_ms: estimate_eta(started.elapsed(), page_number, profiles.len(), total_count, page_size),
+            });
+
+            // Pagination; a cursor that doesn't move would page forever
+            if page.next_cursor.is_some() && page.next_cursor == cursor {
+                return Err(SchemaError(format!("{}: page {} repeated its cursor", source.name(), page_number)).into());
+            }
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+
+            // Stealth delay between pages, and a longer break every few
+            let pace = self.read_pace(scan);
+            let delay = pace.delay();
+            sink.report(ProgressEvent::Wait {
+                stage,
+                duration_ms: delay.as_millis() as u64,
+                reason: WaitReason::Pacing,
+            });
+            self.pause(delay).await;
+
+            if let Some(pause) = pace.break_after(scan.sent.load(Ordering::Relaxed)) {
+                sink.report(ProgressEvent::Wait {
+                    stage,
+                    duration_ms: pause.as_millis() as u64,
+                    reason: WaitReason::Break,
+                });
+                self.pause(pause).await;
+            }
+        }
+
+        sink.report(ProgressEvent::Done { stage, total: profiles.len() });
+        Ok(profiles)
+    }
+
+    /// Fetch one page, retrying network errors, 429 and 5xx with backoff
+    async fn fetch_page(
+        &self,
+        source: &dyn ConnectionSource,
+        request: &PageRequest,
+        stage: &'static str,
+        page: u32,
+        scan: &ScanPace,
+        sink: &dyn ProgressSink,
+    ) -> Result<Value> {
+        let url = self.endpoint_url(&request.path);
+        let query: Vec<(&str, &str)> = request.query.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        let mut attempt = 0;
+
+        loop {
+            let cap = self.read_pace(scan).session_cap;
+            if scan.sent.fetch_add(1, Ordering::Relaxed) >= cap {
+                return Err(anyhow!("The {} pacing profile allows {} requests per scan", scan.profile, cap));
+            }
+            self.record_request(RequestKind::Read);
+            let result = self.get(&url, &query).await;
+
+            let reason = match result {
+                Ok(resp) => {
+                    let status = resp.status;
+                    // Deprecated hashes / removed endpoints answer 400 or 404
+                    if status == StatusCode::BAD_REQUEST || status == StatusCode::NOT_FOUND {
+                        return Err(SchemaError(format!("{} rejected the request ({})", source.name(), status)).into());
+                    }
+                    if status != StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+                        return resp.json();
+                    }
+                    format!("HTTP {}", status)
+                }
+                Err(e) => e.to_string(),
+            };
+
+            attempt += 1;
+            if attempt > MAX_RETRIES {
+                return Err(anyhow!("{} page {} failed after {} retries: {}", stage, page, MAX_RETRIES, reason));
+            }
+
+            log::warn!("{} page {}: retry {} ({})", stage, page, attempt, reason);
+            sink.report(ProgressEvent::Retry { stage, page, attempt, reason });
+
+            let backoff = RETRY_BACKOFF * 2u32.pow(attempt - 1);
+            sink.report(ProgressEvent::Wait {
+                stage,
+                duration_ms: backoff.as_millis() as u64,
+                reason: WaitReason::Backoff,
+            });
+            self.pause(backoff).await;
+        }
+    }
+
+    /// Estimate the page requests a scan of `user_id` needs from its
+    /// follower/following counts
+    pub async fn estimate_scan(&self, user_id: &str) -> Result<ScanEstimate> {
+        let url = self.endpoint_url(&self.endpoints.paths.user_info.replace("{user_id}", user_id));
+        self.record_request(RequestKind::Lookup);
+
+        let resp = self.get(&url, &[]).await?;
+        if !resp.status.is_success() {
+            return Err(anyhow!("User info failed: {}", resp.status));
+        }
+
+        let json: Value = resp.json()?;
+        let user = &json["user"];
+        let count = |field: &str| {
+            user[field]
+                .as_u64()
+                .ok_or_else(|| anyhow!("User info for {} has no {}", user_id, field))
+        };
+        Ok(ScanEstimate::new(count("follower_count")?, count("following_count")?, self.settings.get().page_size))
+    }
+
+    /// Whether a scan of `user_id` fits in the remaining read budget
+    pub async fn preflight_scan(&self, user_id: &str, pacing: Option<&str>) -> Result<ScanPreflight> {
+        let ledger = self.ledger.as_ref().ok_or_else(|| anyhow!("No request ledger attached"))?;
+        let reads = self.pacing_profile(pacing)?.reads;
+        let estimate = self.estimate_scan(user_id).await?;
+        ledger.preflight(self.ledger_account(), estimate, &reads)
+    }
+
+    /// Refuse a scan that would run out of budget or hit the pacing cap
+    /// halfway. Counts that can't be fetched (e.g. a private account) don't
+    /// block the scan.
+    async fn ensure_budget(&self, user_id: &str, scan: &ScanPace) -> Result<()> {
+        let estimate = match self.estimate_scan(user_id).await {
+            Ok(estimate) => estimate,
+            Err(e) => {
+                log::warn!("Skipping budget pre-flight: {}", e);
+                return Ok(());
+            }
+        };
+
+        let reads = self.read_pace(scan);
+        let cap = reads.session_cap;
+        if estimate.requests > cap {
+            return Err(anyhow!(
+                "Scan needs about {} requests but the {} pacing profile allows {} per scan",
+                estimate.requests,
+                scan.profile,
+                cap
+            ));
+        }
+        if let Some(ledger) = &self.ledger {
+            let check = ledger.preflight(self.ledger_account(), estimate, &reads)?;
+            if !check.fits {
+                return Err(BudgetExceeded(check).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Complete Traitor Scan
+    pub async fn find_traitors(&self, user_id: &str, sink: &dyn ProgressSink) -> Result<ScanResult> {
+        let scan = self.scan_pace(None)?;
+        self.ensure_budget(user_id, &scan).await?;
+        let start = std::time::Instant::now();
+
+        // Fetch both lists
+        let followers = self.fetch_connections(user_id, Direction::Followers, &scan, sink).await?;
+        let following = self.fetch_connections(user_id, Direction::Following, &scan, sink).await?;
+
+        // Find traitors: following but not in followers
+        let traitors = non_mutuals(&followers, &following);
+
+        let scan_time_ms = start.elapsed().as_millis() as u64;
+
+        Ok(ScanResult {
+            traitors,
+            total_followers: followers.len() as u32,
+            total_following: following.len() as u32,
+            scan_time_ms,
+        })
+    }
+
+    /// Fetch both lists into a snapshot that can be saved and diffed later,
+    /// paced by the named profile (the settings' one for `None`)
+    pub async fn fetch_snapshot(
+        &self,
+        user_id: &str,
+        pacing: Option<&str>,
+        sink: &dyn ProgressSink,
+    ) -> Result<Snapshot> {
+        let scan = self.scan_pace(pacing)?;
+        log::info!("Scanning {} with {} pacing", user_id, scan.profile);
+
+        let lists = async {
+            self.ensure_budget(user_id, &scan).await?;
+            let followers = self.fetch_connections(user_id, Direction::Followers, &scan, sink).await?;
+            let following = self.fetch_connections(user_id, Direction::Following, &scan, sink).await?;
+            anyhow::Ok((followers, following))
+        };
+        let (followers, following) = lists.await.inspect_err(|e| log::error!("Scan of {} failed: {}", user_id, e))?;
+        log::info!(
+            "Scanned {}: {} followers, {} following, {} requests",
+            user_id,
+            followers.len(),
+            following.len(),
+            scan.sent.load(Ordering::Relaxed)
+        );
+
+        Ok(Snapshot {
+            user_id: user_id.to_string(),
+            taken_at: unix_now(),
+            source: SnapshotSource::Scan,
+            followers,
+            following,
+        })
+    }
+
+    /// Unfollow a user
+    pub async fn unfollow_user(&self, target_user_id: &str) -> Result<bool> {
+        if !self.cookies_loaded {
+            return Err(anyhow!("Session not loaded"));
+        }
+
+        let url = self.endpoint_url(
+            &self.endpoints.paths.friendships_destroy.replace("{user_id}", target_user_id),
+        );
+
+        // get_headers carries the X-CSRFToken writes need
+        let headers = self.get_headers();
+
+        // Take a slot before waiting, so clones unfollowing at the same time
+        // can't overshoot the cap or skip a break
+        let profile = self.pacing_profile(None)?;
+        let cap = profile.writes.session_cap;
+        let sent = self
+            .writes_sent
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| (n < cap).then_some(n + 1))
+            .map_err(|_| anyhow!("The {} pacing profile allows {} unfollows per session", profile.name, cap))?;
+
+        // Stealth delay before action, and a longer break every few
+        self.pause(profile.writes.delay()).await;
+        if let Some(pause) = profile.writes.break_after(sent) {
+            self.pause(pause).await;
+        }
+
+        let params = [("user_id", target_user_id)];
+        self.record_request(RequestKind::Write);
+
+        let resp = self.send(Method::POST, &url, &[], headers, &params).await?;
+
+        let status = resp.status;
+        if status.is_success() {
+            log::info!("Unfollowed user {}", target_user_id);
+            Ok(true)
+        } else {
+            let text = resp.text();
+            log::error!("Unfollow failed: {} - {}", status, redact(&text));
+            Err(anyhow!("Unfollow failed: {}", status))
+        }
+    }
+
+    /// Proxy profile picture to avoid CORS issues, returns base64 data URL
+    pub async fn proxy_profile_pic(&self, pic_url: &str) -> Result<String> {
+        // Build a simple request to fetch the image
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("image/webp,image/avif,image/*,*/*;q=0.8"));
+        headers.insert(REFERER, HeaderValue::from_str(&format!("https://{}/", API_DOMAIN))?);
+        let resp = self.send(Method::GET, pic_url, &[], headers, &[]).await?;
+
+        if !resp.status.is_success() {
+            return Err(anyhow!("Failed to fetch image: {}", resp.status));
+        }
+
+        let content_type = resp
+            .headers
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("image/jpeg")
+            .to_string();
+
+        // Convert to base64 data URL
+        use base64::{Engine, engine::general_purpose};
+        let b64 = general_purpose::STANDARD.encode(&resp.body);
+        let data_url = format!("data:{};base64,{}", content_type, b64);
+
+        Ok(data_url)
+    }
+}
+
+impl Default for GhostClient {
+    fn default() -> Self {
+        Self::new().expect("Failed to create default GhostClient")
+    }
+}
+
@@ -0,0 +1,196 @@
+//! # INSTAFollows Ultimate - Endpoint Definitions
+//!
+//! Query hashes, app identifiers and endpoint paths used by the Ghost Client.
+//! The compiled-in values are the fallback; a versioned JSON file can override
+//! any of them so a broken endpoint can be hot-fixed without a rebuild.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::sources::{source_by_name, FRIENDSHIPS_SOURCE, GRAPHQL_SOURCE};
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Override file looked up at startup (next to the saved session)
+pub const ENDPOINTS_FILE: &str = "instafollows_endpoints.json";
+
+/// Highest config version this build understands
+pub const ENDPOINTS_VERSION: u32 = 1;
+
+const DEFAULT_WEB_APP_ID: &str = "936619743392459";
+const DEFAULT_ASBD_ID: &str = "129477";
+
+// GraphQL Query Hashes (may change - override them in the endpoints file)
+const DEFAULT_FOLLOWERS_HASH: &str = "c76146de99bb02f6415203be841dd25a";
+const DEFAULT_FOLLOWING_HASH: &str = "d04b0a864b4b54837c0d870b0e77e076";
+
+// ============================================
+// DATA STRUCTURES
+// ============================================
+
+/// Endpoint paths, relative to the API domain
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct EndpointPaths {
+    pub web_profile_info: String,
+    /// Must contain the `{user_id}` placeholder
+    pub user_info: String,
+    pub edit_web_form_data: String,
+    pub graphql_query: String,
+    /// Must contain the `{user_id}` placeholder
+    pub friendships_destroy: String,
+    /// Must contain the `{user_id}` placeholder
+    pub friendships_followers: String,
+    /// Must contain the `{user_id}` placeholder
+    pub friendships_following: String,
+}
+
+impl Default for EndpointPaths {
+    fn default() -> Self {
+        EndpointPaths {
+            web_profile_info: "/api/v1/users/web_profile_info/".to_string(),
+            user_info: "/api/v1/users/{user_id}/info/".to_string(),
+            edit_web_form_data: "/api/v1/accounts/edit/web_form_data/".to_string(),
+            graphql_query: "/graphql/query".to_string(),
+            friendships_destroy: "/api/v1/friendships/destroy/{user_id}/".to_string(),
+            friendships_followers: "/api/v1/friendships/{user_id}/followers/".to_string(),
+            friendships_following: "/api/v1/friendships/{user_id}/following/".to_string(),
+        }
+    }
+}
+
+/// Full endpoint configuration. Missing fields in the override file fall back
+/// to the compiled-in values.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct EndpointConfig {
+    pub version: u32,
+    pub web_app_id: String,
+    pub asbd_id: String,
+    pub followers_hash: String,
+    pub following_hash: String,
+    pub paths: EndpointPaths,
+    /// Connection sources to try, in order (see `sources`)
+    pub sources: Vec<String>,
+}
+
+impl Default for EndpointConfig {
+    fn default() -> Self {
+        EndpointConfig {
+            version: ENDPOINTS_VERSION,
+            web_app_id: DEFAULT_WEB_APP_ID.to_string(),
+            asbd_id: DEFAULT_ASBD_ID.to_string(),
+            followers_hash: DEFAULT_FOLLOWERS_HASH.to_string(),
+            following_hash: DEFAULT_FOLLOWING_HASH.to_string(),
+            paths: EndpointPaths::default(),
+            sources: vec![GRAPHQL_SOURCE.to_string(), FRIENDSHIPS_SOURCE.to_string()],
+        }
+    }
+}
+
+// ============================================
+// LOADING & VALIDATION
+// ============================================
+
+impl EndpointConfig {
+    /// Load and validate an override file
+    pub fn load(file_path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(file_path.as_ref())?;
+        let config: EndpointConfig = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| anyhow!("Invalid endpoints file: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load the override file if present, falling back to the compiled-in
+    /// values when it is missing or fails validation
+    pub fn load_or_default(file_path: impl AsRef<Path>) -> Self {
+        let file_path = file_path.as_ref();
+        if !file_path.exists() {
+            return Self::default();
+        }
+
+        match Self::load(file_path) {
+            Ok(config) => {
+                log::info!("Loaded endpoint overrides from {}", file_path.display());
+                config
+            }
+            Err(e) => {
+                log::warn!("Ignoring endpoint overrides ({}), using built-in values", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Reject configs this build can't use safely
+    pub fn validate(&self) -> Result<()> {
+        if self.version == 0 || self.version > ENDPOINTS_VERSION {
+            return Err(anyhow!(
+                "Unsupported endpoints version {} (supported: 1-{})",
+                self.version,
+                ENDPOINTS_VERSION
+            ));
+        }
+
+        for (name, value) in [("web_app_id", &self.web_app_id), ("asbd_id", &self.asbd_id)] {
+            if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+                return Err(anyhow!("{} must be numeric, got {:?}", name, value));
+            }
+        }
+
+        for (name, value) in [
+            ("followers_hash", &self.followers_hash),
+            ("following_hash", &self.following_hash),
+        ] {
+            if value.len() != 32 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(anyhow!("{} must be a 32-character hex hash, got {:?}", name, value));
+            }
+        }
+
+        let paths = &self.paths;
+        for (name, value) in [
+            ("paths.web_profile_info", &paths.web_profile_info),
+            ("paths.user_info", &paths.user_info),
+            ("paths.edit_web_form_data", &paths.edit_web_form_data),
+            ("paths.graphql_query", &paths.graphql_query),
+            ("paths.friendships_destroy", &paths.friendships_destroy),
+            ("paths.friendships_followers", &paths.friendships_followers),
+            ("paths.friendships_following", &paths.friendships_following),
+        ] {
+            // Paths are always joined to API_DOMAIN - never allow a different host
+            if !value.starts_with('/') || value.contains("://") || value.contains(char::is_whitespace) {
+                return Err(anyhow!("{} must be an absolute path, got {:?}", name, value));
+            }
+        }
+
+        for (name, value) in [
+            ("paths.user_info", &paths.user_info),
+            ("paths.friendships_destroy", &paths.friendships_destroy),
+            ("paths.friendships_followers", &paths.friendships_followers),
+            ("paths.friendships_following", &paths.friendships_following),
+        ] {
+            if !value.contains("{user_id}") {
+                return Err(anyhow!("{} must contain {{user_id}}", name));
+            }
+        }
+
+        if self.sources.is_empty() {
+            return Err(anyhow!("sources must list at least one connection source"));
+        }
+        if let Some(unknown) = self.sources.iter().find(|s| source_by_name(s).is_none()) {
+            return Err(anyhow!(
+                "Unknown connection source {:?} (expected {:?} or {:?})",
+                unknown,
+                GRAPHQL_SOURCE,
+                FRIENDSHIPS_SOURCE
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+//! # INSTAFollows Ultimate - Filter Rules
+//!
+//! Serializable rules over `Profile` flags, categories and names, grouped into
+//! named rule sets that can be saved, shared and applied to any scan or
+//! snapshot.
+
+use anyhow::{anyhow, Result};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::Relationships;
+use crate::models::Profile;
+
+// ============================================
+// RULES
+// ============================================
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Rule {
+    Verified,
+    Private,
+    BusinessAccount,
+    ProfessionalAccount,
+    /// Any category label set
+    HasCategory,
+    /// Not verified, business, professional or categorised (same split as the UI)
+    Personal,
+    /// Category label equals one of these (case-insensitive)
+    CategoryIn { categories: Vec<String> },
+    /// Regex over the username (case-insensitive)
+    UsernameMatches { pattern: String },
+    /// Regex over the display name (case-insensitive)
+    FullNameMatches { pattern: String },
+    Not { rule: Box<Rule> },
+    AnyOf { rules: Vec<Rule> },
+    AllOf { rules: Vec<Rule> },
+}
+
+/// A profile is kept when it matches every `include` rule (none = everyone)
+/// and no `exclude` rule
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct RuleSet {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub include: Vec<Rule>,
+    #[serde(default)]
+    pub exclude: Vec<Rule>,
+}
+
+// ============================================
+// COMPILATION
+// ============================================
+
+/// Rule with its regexes compiled
+enum Compiled {
+    Verified,
+    Private,
+    BusinessAccount,
+    ProfessionalAccount,
+    HasCategory,
+    Personal,
+    CategoryIn(Vec<String>),
+    UsernameMatches(Regex),
+    FullNameMatches(Regex),
+    Not(Box<Compiled>),
+    AnyOf(Vec<Compiled>),
+    AllOf(Vec<Compiled>),
+}
+
+fn compile_regex(pattern: &str) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(1 << 20)
+        .build()
+        .map_err(|e| anyhow!("Invalid pattern {:?}: {}", pattern, e))
+}
+
+impl Rule {
+    fn compile(&self) -> Result<Compiled> {
+        Ok(match self {
+            Rule::Verified => Compiled::Verified,
+            Rule::Private => Compiled::Private,
+            Rule::BusinessAccount => Compiled::BusinessAccount,
+            Rule::ProfessionalAccount => Compiled::ProfessionalAccount,
+            Rule::HasCategory => Compiled::HasCategory,
+            Rule::Personal => Compiled::Personal,
+            Rule::CategoryIn { categories } => {
+                Compiled::CategoryIn(categories.iter().map(|c| c.to_lowercase()).collect())
+            }
+            Rule::UsernameMatches { pattern } => Compiled::UsernameMatches(compile_regex(pattern)?),
+            Rule::FullNameMatches { pattern } => Compiled::FullNameMatches(compile_regex(pattern)?),
+            Rule::Not { rule } => Compiled::Not(Box::new(rule.compile()?)),
+            Rule::AnyOf { rules } => Compiled::AnyOf(rules.iter().map(Rule::compile).collect::<Result<_>>()?),
+            Rule::AllOf { rules } => Compiled::AllOf(rules.iter().map(Rule::compile).collect::<Result<_>>()?),
+        })
+    }
+}
+
+impl Compiled {
+    fn matches(&self, p: &Profile) -> bool {
+        let has_category = p.category_name.as_deref().is_some_and(|c| !c.is_empty());
+
+        match self {
+            Compiled::Verified => p.is_verified,
+            Compiled::Private => p.is_private,
+            Compiled::BusinessAccount => p.is_business_account,
+            Compiled::ProfessionalAccount => p.is_professional_account,
+            Compiled::HasCategory => has_category,
+            Compiled::Personal => {
+                !(p.is_verified || p.is_business_account || p.is_professional_account || has_category)
+            }
+            Compiled::CategoryIn(categories) => p
+                .category_name
+                .as_deref()
+                .is_some_and(|c| categories.contains(&c.to_lowercase())),
+            Compiled::UsernameMatches(re) => re.is_match(&p.username),
+            Compiled::FullNameMatches(re) => re.is_match(&p.full_name),
+            Compiled::Not(rule) => !rule.matches(p),
+            Compiled::AnyOf(rules) => rules.iter().any(|r| r.matches(p)),
+            Compiled::AllOf(rules) => rules.iter().all(|r| r.matches(p)),
+        }
+    }
+}
+
+// ============================================
+// FILTER
+// ============================================
+
+/// A validated, ready-to-apply rule set
+pub struct Filter {
+    include: Vec<Compiled>,
+    exclude: Vec<Compiled>,
+}
+
+impl RuleSet {
+    /// Validate the rule set and compile its patterns
+    pub fn compile(&self) -> Result<Filter> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow!("Rule set needs a name"));
+        }
+
+        let compile_all = |rules: &[Rule]| rules.iter().map(Rule::compile).collect::<Result<Vec<_>>>();
+        Ok(Filter {
+            include: compile_all(&self.include).map_err(|e| anyhow!("{}: {}", self.name, e))?,
+            exclude: compile_all(&self.exclude).map_err(|e| anyhow!("{}: {}", self.name, e))?,
+        })
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, profile: &Profile) -> bool {
+        self.include.iter().all(|r| r.matches(profile)) && !self.exclude.iter().any(|r| r.matches(profile))
+    }
+
+    pub fn apply(&self, profiles: &[Profile]) -> Vec<Profile> {
+        profiles.iter().filter(|p| self.matches(p)).cloned().collect()
+    }
+
+    /// Filter every list of a relationship report (counts stay unfiltered)
+    pub fn apply_relationships(&self, report: &Relationships) -> Relationships {
+        let mut filtered = report.clone();
+        filtered.mutuals.retain(|p| self.matches(p));
+        filtered.not_following_back.retain(|p| self.matches(p));
+        filtered.fans.retain(|p| self.matches(p));
+        if let Some(changes) = filtered.changes.as_mut() {
+            changes.new_followers.retain(|p| self.matches(p));
+            changes.lost_followers.retain(|p| self.matches(p));
+            changes.new_following.retain(|p| self.matches(p));
+            changes.dropped_following.retain(|p| self.matches(p));
+        }
+        filtered
+    }
+}
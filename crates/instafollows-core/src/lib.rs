@@ -0,0 +1,30 @@
+//! # INSTAFollows Core
+//!
+//! The Ghost Client, data models, analysis and storage behind INSTAFollows
+//! Ultimate, free of any UI dependency. Used by the Tauri desktop app and the
+//! `instafollows` CLI.
+
+pub mod analysis;
+pub mod archive;
+pub mod budget;
+pub mod client;
+pub mod endpoints;
+pub mod filters;
+pub mod logging;
+pub mod models;
+pub mod notify;
+pub mod pacing;
+pub mod progress;
+pub mod recording;
+pub mod redact;
+pub mod replay;
+pub mod schedule;
+pub mod search;
+pub mod settings;
+pub mod stats;
+pub mod sources;
+pub mod storage;
+pub mod store;
+
+pub use client::GhostClient;
+pub use models::{Profile, ScanResult, Snapshot};
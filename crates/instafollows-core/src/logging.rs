@@ -0,0 +1,196 @@
+//! # INSTAFollows Ultimate - Diagnostics Log
+//!
+//! JSON-lines log written to a size-rotated file, with session cookies and
+//! CSRF tokens scrubbed before anything reaches the disk. Exported logs are
+//! safe to attach to bug reports.
+
+use anyhow::{anyhow, Result};
+use chrono::{SecondsFormat, Utc};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::redact::redact;
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Current log file; rotated copies get `.1`, `.2`, ... (oldest last)
+pub const LOG_FILE: &str = "instafollows.log";
+
+/// Rotate once the current file reaches this size
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Rotated files kept next to the current one
+const MAX_ROTATED_FILES: usize = 4;
+
+// ============================================
+// LEVELS
+// ============================================
+
+/// Parse a level name ("off", "error", "warn", "info", "debug", "trace")
+pub fn parse_level(level: &str) -> Result<LevelFilter> {
+    LevelFilter::from_str(level).map_err(|_| anyhow!("Unknown log level: {}", level))
+}
+
+/// Change the level of the running logger
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+// ============================================
+// FILE LOGGER
+// ============================================
+
+struct LogFile {
+    file: File,
+    size: u64,
+}
+
+/// `log` backend writing scrubbed JSON lines to `dir/instafollows.log`
+pub struct FileLogger {
+    dir: PathBuf,
+    current: Mutex<LogFile>,
+}
+
+impl FileLogger {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let current = open_log(&dir.join(LOG_FILE))?;
+        Ok(FileLogger {
+            dir,
+            current: Mutex::new(current),
+        })
+    }
+
+    /// Shift `.1` -> `.2`, ..., the current file -> `.1`, and start afresh
+    fn rotate(&self, current: &mut LogFile) -> Result<()> {
+        let path = self.dir.join(LOG_FILE);
+        let _ = fs::remove_file(rotated_path(&path, MAX_ROTATED_FILES));
+        for n in (1..MAX_ROTATED_FILES).rev() {
+            let _ = fs::rename(rotated_path(&path, n), rotated_path(&path, n + 1));
+        }
+        fs::rename(&path, rotated_path(&path, 1))?;
+        *current = open_log(&path)?;
+        Ok(())
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Dependencies (HTTP stack, TLS) only get through with warnings
+        metadata.level() <= log::max_level()
+            && (metadata.target().starts_with("instafollows") || metadata.level() <= Level::Warn)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = serde_json::json!({
+            "ts": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            "level": record.level().as_str(),
+            "target": record.target(),
+            "msg": redact(&record.args().to_string()),
+        })
+        .to_string();
+
+        let rotation = {
+            let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+            let mut rotation = Ok(());
+            if current.size > 0 && current.size + line.len() as u64 >= MAX_FILE_BYTES {
+                rotation = self.rotate(&mut current);
+                if rotation.is_err() {
+                    // Keep appending; try again after another full file
+                    current.size = 0;
+                }
+            }
+            if writeln!(current.file, "{}", line).is_ok() {
+                current.size += line.len() as u64 + 1;
+            }
+            rotation
+        };
+
+        // Logged once the file is released, as logging takes it again
+        if let Err(e) = rotation {
+            log::warn!("Log rotation failed: {}", e);
+        }
+    }
+
+    fn flush(&self) {
+        let _ = self.current.lock().unwrap_or_else(|e| e.into_inner()).file.flush();
+    }
+}
+
+fn open_log(path: &Path) -> Result<LogFile> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok(LogFile { file, size })
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Install the file logger for the whole process
+pub fn init_logging(dir: impl AsRef<Path>, level: LevelFilter) -> Result<()> {
+    let logger = FileLogger::open(dir)?;
+    log::set_logger(Box::leak(Box::new(logger))).map_err(|_| anyhow!("A logger is already installed"))?;
+    set_level(level);
+    Ok(())
+}
+
+// ============================================
+// EXPORT
+// ============================================
+
+/// Copy every kept log file, oldest first, into one file for a bug report.
+/// Returns the number of lines written.
+pub fn export_logs(dir: impl AsRef<Path>, dest: impl AsRef<Path>) -> Result<usize> {
+    log::logger().flush();
+    let path = dir.as_ref().join(LOG_FILE);
+
+    let mut files: Vec<PathBuf> = (1..=MAX_ROTATED_FILES).rev().map(|n| rotated_path(&path, n)).collect();
+    files.push(path);
+
+    let mut out = File::create(dest.as_ref())?;
+    let mut lines = 0;
+    for file in files.iter().filter(|f| f.exists()) {
+        // Scrubbed again in case an older build wrote the file
+        for line in fs::read_to_string(file)?.lines() {
+            writeln!(out, "{}", redact_line(line))?;
+            lines += 1;
+        }
+    }
+    Ok(lines)
+}
+
+/// Scrub the fields of a JSON line rather than its text, where quotes
+/// inside messages are escaped and would hide secrets from `redact`
+fn redact_line(line: &str) -> String {
+    match serde_json::from_str::<Value>(line) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => redact(line),
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::String(text) => *text = redact(text),
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        Value::Object(fields) => fields.values_mut().for_each(redact_value),
+        _ => {}
+    }
+}
@@ -0,0 +1,118 @@
+//! # INSTAFollows Ultimate - Data Models
+//!
+//! Types shared by the client, the analysis and the storage modules.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+use crate::redact::REDACTED;
+
+// ============================================
+// DATA STRUCTURES
+// ============================================
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Profile {
+    pub id: String,
+    pub username: String,
+    pub full_name: String,
+    pub profile_pic_url: String,
+    pub profile_pic_url_hd: Option<String>,
+    pub is_verified: bool,
+    pub is_private: bool,
+    pub is_business_account: bool,
+    pub is_professional_account: bool,
+    pub category_name: Option<String>,
+}
+
+impl Profile {
+    /// Build a profile from a user node (GraphQL edge, friendships API or
+    /// web_profile_info - they share field names, not the id field)
+    pub fn from_json(node: &Value) -> Self {
+        let id = match (&node["id"], &node["pk_id"], &node["pk"]) {
+            (Value::String(id), _, _) | (_, Value::String(id), _) | (_, _, Value::String(id)) => id.clone(),
+            (_, _, Value::Number(pk)) => pk.to_string(),
+            _ => String::new(),
+        };
+
+        Profile {
+            id,
+            username: node["username"].as_str().unwrap_or("").to_string(),
+            full_name: node["full_name"].as_str().unwrap_or("").to_string(),
+            profile_pic_url: node["profile_pic_url"].as_str().unwrap_or("").to_string(),
+            profile_pic_url_hd: node["profile_pic_url_hd"].as_str().map(|s| s.to_string()),
+            is_verified: node["is_verified"].as_bool().unwrap_or(false),
+            is_private: node["is_private"].as_bool().unwrap_or(false),
+            is_business_account: node["is_business_account"].as_bool().unwrap_or(false),
+            is_professional_account: node["is_professional_account"].as_bool().unwrap_or(false),
+            category_name: node["category_name"].as_str().map(|s| s.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CookieItem {
+    pub name: String,
+    pub value: String,
+    pub domain: Option<String>,
+}
+
+/// Cookie values are session secrets: never printed
+impl fmt::Debug for CookieItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CookieItem")
+            .field("name", &self.name)
+            .field("value", &REDACTED)
+            .field("domain", &self.domain)
+            .finish()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ScanResult {
+    pub traitors: Vec<Profile>,
+    pub total_followers: u32,
+    pub total_following: u32,
+    pub scan_time_ms: u64,
+}
+
+// ============================================
+// SNAPSHOTS
+// ============================================
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotSource {
+    /// Live scan through the Ghost Client
+    Scan,
+    /// Imported from a "Download your information" archive (no user IDs)
+    Archive,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Snapshot {
+    /// Scanned account (empty for archives imported without one)
+    pub user_id: String,
+    /// Unix timestamp (seconds)
+    pub taken_at: u64,
+    pub source: SnapshotSource,
+    pub followers: Vec<Profile>,
+    pub following: Vec<Profile>,
+}
+
+/// Changes between two snapshots of the same account
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SnapshotDiff {
+    pub new_followers: Vec<Profile>,
+    pub lost_followers: Vec<Profile>,
+    pub new_following: Vec<Profile>,
+    pub dropped_following: Vec<Profile>,
+}
+
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,126 @@
+//! # INSTAFollows Ultimate - Change Notifications
+//!
+//! Turns the changes between two stored scans into a short notification
+//! ("3 new followers, 2 unfollowed you, 1 no longer mutual"), honouring
+//! per-category toggles and thresholds.
+
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::{diff, lost_mutuals};
+use crate::models::Snapshot;
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Store setting holding the `NotificationConfig`
+pub const NOTIFICATIONS_SETTING: &str = "notifications";
+
+pub const NOTIFICATION_TITLE: &str = "INSTAFollows Ultimate";
+
+// ============================================
+// CONFIGURATION
+// ============================================
+
+/// Mention a category once at least `threshold` changes happened
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CategoryAlert {
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    #[serde(default = "one")]
+    pub threshold: usize,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+fn one() -> usize {
+    1
+}
+
+impl Default for CategoryAlert {
+    fn default() -> Self {
+        CategoryAlert {
+            enabled: true,
+            threshold: 1,
+        }
+    }
+}
+
+impl CategoryAlert {
+    fn triggers(&self, count: usize) -> bool {
+        self.enabled && count > 0 && count >= self.threshold
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct NotificationConfig {
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub new_followers: CategoryAlert,
+    #[serde(default)]
+    pub lost_followers: CategoryAlert,
+    #[serde(default)]
+    pub lost_mutuals: CategoryAlert,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        NotificationConfig {
+            enabled: true,
+            new_followers: CategoryAlert::default(),
+            lost_followers: CategoryAlert::default(),
+            lost_mutuals: CategoryAlert::default(),
+        }
+    }
+}
+
+// ============================================
+// SUMMARY
+// ============================================
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct ChangeCounts {
+    pub new_followers: usize,
+    pub lost_followers: usize,
+    pub lost_mutuals: usize,
+}
+
+impl ChangeCounts {
+    pub fn between(old: &Snapshot, new: &Snapshot) -> Self {
+        let changes = diff(old, new);
+        ChangeCounts {
+            new_followers: changes.new_followers.len(),
+            lost_followers: changes.lost_followers.len(),
+            lost_mutuals: lost_mutuals(old, new).len(),
+        }
+    }
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+impl NotificationConfig {
+    /// Notification body, `None` when nothing crosses its threshold
+    pub fn message(&self, counts: &ChangeCounts) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+
+        let mut parts = Vec::new();
+        if self.new_followers.triggers(counts.new_followers) {
+            parts.push(plural(counts.new_followers, "new follower", "new followers"));
+        }
+        if self.lost_followers.triggers(counts.lost_followers) {
+            parts.push(format!("{} unfollowed you", counts.lost_followers));
+        }
+        if self.lost_mutuals.triggers(counts.lost_mutuals) {
+            parts.push(format!("{} no longer mutual", counts.lost_mutuals));
+        }
+
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
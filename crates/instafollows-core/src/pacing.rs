@@ -0,0 +1,194 @@
+//! # INSTAFollows Ultimate - Scan Pacing
+//!
+//! Named pacing profiles: how long to wait between requests, when to take a
+//! longer break and how many requests one run may send, set separately for
+//! reads (follower/following pages) and writes (unfollows).
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::settings::{Settings, MIN_DELAY_FLOOR_MS};
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+pub const GENTLE_PROFILE: &str = "gentle";
+pub const STANDARD_PROFILE: &str = "standard";
+pub const OVERNIGHT_PROFILE: &str = "overnight";
+
+pub const BUILTIN_PROFILES: [&str; 3] = [GENTLE_PROFILE, STANDARD_PROFILE, OVERNIGHT_PROFILE];
+
+// ============================================
+// PROFILES
+// ============================================
+
+/// Pacing of one kind of request
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Pace {
+    /// Random pause between requests
+    pub delay_min_ms: u64,
+    pub delay_max_ms: u64,
+    /// Take a longer break after this many requests (0 = never)
+    pub break_every: u32,
+    pub break_ms: u64,
+    /// Most requests in one scan (reads) or one login session (writes)
+    pub session_cap: u32,
+}
+
+impl Pace {
+    pub fn validate(&self) -> Result<()> {
+        if self.delay_min_ms < MIN_DELAY_FLOOR_MS {
+            return Err(anyhow!("Minimum delay can't go below {} ms", MIN_DELAY_FLOOR_MS));
+        }
+        if self.delay_max_ms < self.delay_min_ms {
+            return Err(anyhow!("Maximum delay must be at least the minimum delay"));
+        }
+        if self.break_every > 0 && self.break_ms < self.delay_max_ms {
+            return Err(anyhow!("A break must be longer than the maximum delay"));
+        }
+        if self.session_cap == 0 {
+            return Err(anyhow!("Session cap must be above 0"));
+        }
+        Ok(())
+    }
+
+    /// Random delay before the next request
+    pub fn delay(&self) -> Duration {
+        use rand::Rng;
+        Duration::from_millis(rand::rng().random_range(self.delay_min_ms..=self.delay_max_ms))
+    }
+
+    /// Most requests this pace can send within `window_secs`, always taking
+    /// the shortest delay
+    pub fn max_requests_in(&self, window_secs: u64) -> u64 {
+        let window_ms = window_secs.saturating_mul(1000);
+        let delay = self.delay_min_ms.max(1);
+        if self.break_every == 0 {
+            return window_ms / delay + 1;
+        }
+
+        // A cycle is `break_every` requests, then a break
+        let every = u64::from(self.break_every);
+        let cycle = every * delay + self.break_ms;
+        (window_ms / cycle) * every + (window_ms % cycle / delay + 1).min(every)
+    }
+
+    /// Break due after `sent` requests, if any
+    pub fn break_after(&self, sent: u32) -> Option<Duration> {
+        (self.break_every > 0 && sent > 0 && sent.is_multiple_of(self.break_every))
+            .then(|| Duration::from_millis(self.break_ms))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct PacingProfile {
+    pub name: String,
+    pub reads: Pace,
+    pub writes: Pace,
+}
+
+impl PacingProfile {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow!("Pacing profile needs a name"));
+        }
+        self.reads
+            .validate()
+            .map_err(|e| anyhow!("{} reads: {}", self.name, e))?;
+        self.writes
+            .validate()
+            .map_err(|e| anyhow!("{} writes: {}", self.name, e))
+    }
+
+    /// Few, slow requests with frequent breaks, for small accounts or
+    /// sessions that were recently challenged
+    pub fn gentle() -> Self {
+        PacingProfile {
+            name: GENTLE_PROFILE.to_string(),
+            reads: Pace {
+                delay_min_ms: 2500,
+                delay_max_ms: 5000,
+                break_every: 20,
+                break_ms: 60_000,
+                session_cap: 150,
+            },
+            writes: Pace {
+                delay_min_ms: 20_000,
+                delay_max_ms: 45_000,
+                break_every: 10,
+                break_ms: 300_000,
+                session_cap: 30,
+            },
+        }
+    }
+
+    /// Reads use the delay range from the settings
+    pub fn standard(settings: &Settings) -> Self {
+        PacingProfile {
+            name: STANDARD_PROFILE.to_string(),
+            reads: Pace {
+                delay_min_ms: settings.delay_min_ms,
+                delay_max_ms: settings.delay_max_ms,
+                break_every: 40,
+                break_ms: 30_000.max(settings.delay_max_ms),
+                session_cap: 400,
+            },
+            writes: Pace {
+                delay_min_ms: 5000,
+                delay_max_ms: 12_000,
+                break_every: 20,
+                break_ms: 120_000,
+                session_cap: 60,
+            },
+        }
+    }
+
+    /// Slow but long-running, for large accounts scanned while asleep
+    pub fn overnight() -> Self {
+        PacingProfile {
+            name: OVERNIGHT_PROFILE.to_string(),
+            reads: Pace {
+                delay_min_ms: 6000,
+                delay_max_ms: 15_000,
+                break_every: 25,
+                break_ms: 180_000,
+                session_cap: 2000,
+            },
+            writes: Pace {
+                delay_min_ms: 30_000,
+                delay_max_ms: 90_000,
+                break_every: 10,
+                break_ms: 600_000,
+                session_cap: 100,
+            },
+        }
+    }
+}
+
+/// Built-in or custom profile by name
+pub fn pacing_profile(settings: &Settings, name: &str) -> Result<PacingProfile> {
+    match name {
+        GENTLE_PROFILE => Ok(PacingProfile::gentle()),
+        STANDARD_PROFILE => Ok(PacingProfile::standard(settings)),
+        OVERNIGHT_PROFILE => Ok(PacingProfile::overnight()),
+        _ => settings
+            .pacing_profiles
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unknown pacing profile: {}", name)),
+    }
+}
+
+/// Every profile that can be selected, built-in ones first
+pub fn pacing_profiles(settings: &Settings) -> Vec<PacingProfile> {
+    let mut profiles = vec![
+        PacingProfile::gentle(),
+        PacingProfile::standard(settings),
+        PacingProfile::overnight(),
+    ];
+    profiles.extend(settings.pacing_profiles.iter().cloned());
+    profiles
+}
@@ -0,0 +1,101 @@
+//! # INSTAFollows Ultimate - Progress Reporting
+//!
+//! The network layer reports what it is doing to a `ProgressSink` instead of
+//! a UI handle. The desktop app forwards events to the webview, the CLI prints
+//! them, tests can collect them through a channel.
+
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+
+// ============================================
+// EVENTS
+// ============================================
+
+#[derive(Clone, Copy, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WaitReason {
+    /// Human-like delay between pages
+    Pacing,
+    /// Backing off after a failed request
+    Backoff,
+    /// Longer break every few pages, per the pacing profile
+    Break,
+}
+
+#[derive(Clone, Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// A page was fetched and parsed
+    Page {
+        stage: &'static str,
+        page: u32,
+        current: usize,
+        /// 0 when the source doesn't report a total
+        total: usize,
+        /// Estimated time left for this stage, when the total is known
+        eta_ms: Option<u64>,
+    },
+    /// A page request failed and will be retried
+    Retry {
+        stage: &'static str,
+        page: u32,
+        attempt: u32,
+        reason: String,
+    },
+    /// Sleeping before the next request
+    Wait {
+        stage: &'static str,
+        duration_ms: u64,
+        reason: WaitReason,
+    },
+    /// All pages of a stage were fetched
+    Done { stage: &'static str, total: usize },
+}
+
+/// Estimate the time left from the average time per page so far
+pub fn estimate_eta(elapsed: Duration, pages: u32, current: usize, total: usize, page_size: usize) -> Option<u64> {
+    if total == 0 || pages == 0 || page_size == 0 {
+        return None;
+    }
+    let remaining_pages = total.saturating_sub(current).div_ceil(page_size) as u64;
+    let per_page_ms = elapsed.as_millis() as u64 / pages as u64;
+    Some(per_page_ms * remaining_pages)
+}
+
+// ============================================
+// SINKS
+// ============================================
+
+pub trait ProgressSink: Send + Sync {
+    fn report(&self, event: ProgressEvent);
+}
+
+/// Discards everything
+pub struct NoopSink;
+
+impl ProgressSink for NoopSink {
+    fn report(&self, _event: ProgressEvent) {}
+}
+
+/// Writes events to the `log` facade
+pub struct LogSink;
+
+impl ProgressSink for LogSink {
+    fn report(&self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::Retry { .. } => log::warn!("{:?}", event),
+            ProgressEvent::Wait { .. } => log::debug!("{:?}", event),
+            _ => log::info!("{:?}", event),
+        }
+    }
+}
+
+/// Forwards events to a channel (a closed receiver is ignored)
+pub struct ChannelSink(pub UnboundedSender<ProgressEvent>);
+
+impl ProgressSink for ChannelSink {
+    fn report(&self, event: ProgressEvent) {
+        let _ = self.0.send(event);
+    }
+}
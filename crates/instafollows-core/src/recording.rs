@@ -0,0 +1,294 @@
+//! # INSTAFollows Ultimate - HTTP Recorder
+//!
+//! Opt-in capture of every request the Ghost Client sends and the response
+//! it got, appended to a HAR-like JSON lines file: a header line, then one
+//! line per exchange. Secrets are redacted on the way in, so a recording can
+//! be attached to a bug report or kept as a fixture.
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine};
+use chrono::{SecondsFormat, Utc};
+use rquest::header::HeaderMap;
+use rquest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use url::Url;
+
+use crate::redact::{redact, redact_header};
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Default recording file name
+pub const RECORDING_FILE: &str = "instafollows_recording.jsonl";
+
+pub const RECORDING_VERSION: u32 = 1;
+
+/// Response bodies are cut after this many bytes
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+// ============================================
+// DATA STRUCTURES
+// ============================================
+
+/// A response read in full, from the network or a recording
+pub struct HttpReply {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl HttpReply {
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_slice(&self.body).map_err(|e| anyhow!("Invalid JSON response ({}): {}", self.status, e))
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct NameValue {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Without the query string
+    pub url: String,
+    pub query: Vec<NameValue>,
+    /// Secrets redacted
+    pub headers: Vec<NameValue>,
+    /// Form fields of a POST
+    #[serde(default)]
+    pub form: Vec<NameValue>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<NameValue>,
+    /// UTF-8 text, or base64 when `encoding` says so
+    pub body: String,
+    #[serde(default)]
+    pub encoding: Option<String>,
+    /// Size of the full body, before truncation
+    pub size: usize,
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RecordedEntry {
+    /// RFC 3339
+    pub started: String,
+    pub time_ms: u64,
+    pub request: RecordedRequest,
+    /// `None` when the request failed before an answer (see `error`)
+    pub response: Option<RecordedResponse>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Recording {
+    pub version: u32,
+    pub creator: String,
+    pub entries: Vec<RecordedEntry>,
+}
+
+/// First line of a recording written by the `Recorder`
+#[derive(Serialize, Deserialize)]
+struct RecordingHeader {
+    version: u32,
+    creator: String,
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Recording {
+            version: RECORDING_VERSION,
+            creator: format!("INSTAFollows Ultimate {}", env!("CARGO_PKG_VERSION")),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl Recording {
+    /// Read a recording, either JSON lines as the `Recorder` appends them or
+    /// one document as `save` writes it
+    pub fn load(file_path: impl AsRef<Path>) -> Result<Self> {
+        let file_path = file_path.as_ref();
+        let text = std::fs::read_to_string(file_path)
+            .map_err(|e| anyhow!("Cannot open recording {}: {}", file_path.display(), e))?;
+        let recording = match serde_json::from_str::<Recording>(&text) {
+            Ok(recording) => recording,
+            Err(_) => Self::from_lines(&text)
+                .map_err(|e| anyhow!("Invalid recording {}: {}", file_path.display(), e))?,
+        };
+        if recording.version == 0 || recording.version > RECORDING_VERSION {
+            return Err(anyhow!("Unsupported recording version {}", recording.version));
+        }
+        Ok(recording)
+    }
+
+    pub fn save(&self, file_path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(file_path.as_ref(), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn from_lines(text: &str) -> Result<Self> {
+        let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        let (_, header) = lines.next().ok_or_else(|| anyhow!("empty file"))?;
+        let header: RecordingHeader = serde_json::from_str(header).map_err(|e| anyhow!("header: {}", e))?;
+
+        let entries = lines
+            .map(|(i, line)| serde_json::from_str(line).map_err(|e| anyhow!("line {}: {}", i + 1, e)))
+            .collect::<Result<_>>()?;
+        Ok(Recording {
+            version: header.version,
+            creator: header.creator,
+            entries,
+        })
+    }
+}
+
+// ============================================
+// RECORDER
+// ============================================
+
+/// Appends every exchange to a recording file
+pub struct Recorder {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    /// Start a fresh recording at `path`, replacing any previous one
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = File::create(&path)
+            .map_err(|e| anyhow!("Cannot create recording {}: {}", path.display(), e))?;
+
+        let Recording { version, creator, .. } = Recording::default();
+        file.write_all(json_line(&RecordingHeader { version, creator })?.as_bytes())?;
+        Ok(Recorder {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one exchange as a line. Failures are logged: recording never
+    /// breaks a request.
+    pub fn record(
+        &self,
+        method: &Method,
+        url: &str,
+        headers: &HeaderMap,
+        form: &[(&str, &str)],
+        result: &Result<HttpReply>,
+        elapsed: Duration,
+    ) {
+        let entry = RecordedEntry {
+            started: (Utc::now() - elapsed).to_rfc3339_opts(SecondsFormat::Millis, true),
+            time_ms: elapsed.as_millis() as u64,
+            request: recorded_request(method, url, headers, form),
+            response: result.as_ref().ok().map(recorded_response),
+            error: result.as_ref().err().map(|e| redact(&e.to_string())),
+        };
+
+        // One write per line, so a crash leaves every earlier entry readable
+        let written = json_line(&entry).and_then(|line| {
+            let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+            Ok(file.write_all(line.as_bytes())?)
+        });
+        if let Err(e) = written {
+            log::warn!("Recorder: {}", e);
+        }
+    }
+}
+
+fn json_line(value: &impl Serialize) -> Result<String> {
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    Ok(line)
+}
+
+fn recorded_request(method: &Method, url: &str, headers: &HeaderMap, form: &[(&str, &str)]) -> RecordedRequest {
+    let (base, query) = match Url::parse(url) {
+        Ok(mut parsed) => {
+            let query = parsed
+                .query_pairs()
+                .map(|(name, value)| NameValue {
+                    name: name.into_owned(),
+                    value: redact(&value),
+                })
+                .collect();
+            parsed.set_query(None);
+            (parsed.to_string(), query)
+        }
+        Err(_) => (url.to_string(), Vec::new()),
+    };
+
+    RecordedRequest {
+        method: method.to_string(),
+        url: base,
+        query,
+        headers: redacted_headers(headers),
+        form: form
+            .iter()
+            .map(|(name, value)| NameValue {
+                name: name.to_string(),
+                value: redact(value),
+            })
+            .collect(),
+    }
+}
+
+fn recorded_response(reply: &HttpReply) -> RecordedResponse {
+    let size = reply.body.len();
+    let (body, encoding) = match std::str::from_utf8(&reply.body) {
+        Ok(text) => {
+            let mut end = size.min(MAX_BODY_BYTES);
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            (redact(&text[..end]), None)
+        }
+        Err(_) => (
+            general_purpose::STANDARD.encode(&reply.body[..size.min(MAX_BODY_BYTES)]),
+            Some("base64".to_string()),
+        ),
+    };
+
+    RecordedResponse {
+        status: reply.status.as_u16(),
+        headers: redacted_headers(&reply.headers),
+        body,
+        encoding,
+        size,
+        truncated: size > MAX_BODY_BYTES,
+    }
+}
+
+fn redacted_headers(headers: &HeaderMap) -> Vec<NameValue> {
+    headers
+        .iter()
+        .map(|(name, value)| NameValue {
+            name: name.to_string(),
+            value: redact_header(name.as_str(), value),
+        })
+        .collect()
+}
@@ -0,0 +1,106 @@
+//! # INSTAFollows Ultimate - Secret Redaction
+//!
+//! Wrapper types that keep the session cookie and CSRF token out of
+//! `Debug`/`Display` output, and scrubbing of cookies, tokens and auth
+//! headers in any text headed for a log.
+
+use regex::Regex;
+use rquest::header::{HeaderMap, HeaderValue};
+use std::fmt;
+use std::sync::OnceLock;
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+pub const REDACTED: &str = "[redacted]";
+
+/// Headers whose whole value is a secret
+const SECRET_HEADERS: [&str; 4] = ["cookie", "set-cookie", "x-csrftoken", "authorization"];
+
+// ============================================
+// SECRET TYPES
+// ============================================
+
+macro_rules! secret {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Clone, PartialEq, Eq)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn new(value: impl Into<String>) -> Self {
+                $name(value.into())
+            }
+
+            /// The real value, for the request that needs it
+            pub fn expose(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({})", stringify!($name), REDACTED)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(REDACTED)
+            }
+        }
+    };
+}
+
+secret! {
+    /// The `sessionid` cookie: whoever has it is logged in as the user
+    SessionId
+}
+
+secret! {
+    /// The `csrftoken` cookie, echoed in `X-CSRFToken` on writes
+    CsrfToken
+}
+
+// ============================================
+// SCRUBBING
+// ============================================
+
+/// Mask session cookies, CSRF tokens and auth headers, whether they appear
+/// as cookie pairs, headers or JSON fields
+pub fn redact(text: &str) -> String {
+    static HEADERS: OnceLock<Regex> = OnceLock::new();
+    static SECRETS: OnceLock<Regex> = OnceLock::new();
+
+    let headers = HEADERS.get_or_init(|| {
+        Regex::new(r"(?im)^(\s*(?:cookie|set-cookie|authorization)\s*:\s*).+$").expect("valid redaction pattern")
+    });
+    let secrets = SECRETS.get_or_init(|| {
+        Regex::new(
+            r#"(?i)(\b(?:sessionid|(?:x-)?csrf[_-]?token|rur|mid|ig_did)"?\s*[=:]\s*"?)[^;,"\s&}]+"#,
+        )
+        .expect("valid redaction pattern")
+    });
+
+    let text = headers.replace_all(text, format!("${{1}}{}", REDACTED));
+    secrets.replace_all(&text, format!("${{1}}{}", REDACTED)).into_owned()
+}
+
+/// A header value safe to log; `name` is lowercase, as `HeaderName` gives it
+pub fn redact_header(name: &str, value: &HeaderValue) -> String {
+    if SECRET_HEADERS.contains(&name) {
+        REDACTED.to_string()
+    } else {
+        redact(value.to_str().unwrap_or("<binary>"))
+    }
+}
+
+/// Request or response headers as one loggable line, secrets masked
+pub fn redact_headers(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, redact_header(name.as_str(), value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,150 @@
+//! # INSTAFollows Ultimate - Replay Transport
+//!
+//! Serves the Ghost Client's requests from a recording (see `recording`)
+//! instead of the network, so a user-reported pagination problem can be
+//! reproduced offline and kept as a regression test.
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine};
+use rquest::header::{HeaderMap, HeaderName, HeaderValue};
+use rquest::{Method, StatusCode};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Mutex;
+use url::Url;
+
+use crate::recording::{HttpReply, NameValue, RecordedEntry, RecordedResponse, Recording};
+use crate::redact::redact;
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Query parameter holding GraphQL variables, compared as JSON
+const VARIABLES_PARAM: &str = "variables";
+
+/// Page-size parameters (friendships `count`, GraphQL `first`), left out of
+/// the match so a recording replays under any page size setting
+const PAGE_SIZE_PARAMS: [&str; 2] = ["count", "first"];
+
+// ============================================
+// REPLAYER
+// ============================================
+
+/// Answers requests with recorded responses. Each entry is served once, in
+/// recorded order among the entries matching the same request, so retries
+/// and repeated pages replay the way they happened.
+pub struct Replayer {
+    entries: Vec<RecordedEntry>,
+    served: Mutex<Vec<bool>>,
+}
+
+impl Replayer {
+    pub fn new(recording: Recording) -> Self {
+        let served = vec![false; recording.entries.len()];
+        Replayer {
+            entries: recording.entries,
+            served: Mutex::new(served),
+        }
+    }
+
+    pub fn load(file_path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(Recording::load(file_path)?))
+    }
+
+    /// Recorded entries not served yet
+    pub fn remaining(&self) -> usize {
+        self.served.lock().unwrap_or_else(|e| e.into_inner()).iter().filter(|s| !**s).count()
+    }
+
+    /// The next unserved response recorded for this method, path and query
+    pub fn reply(&self, method: &Method, url: &Url) -> Result<HttpReply> {
+        let query = normalized_query(url.query_pairs().map(|(n, v)| (n.into_owned(), v.into_owned())));
+
+        let mut served = self.served.lock().unwrap_or_else(|e| e.into_inner());
+        let index = self
+            .entries
+            .iter()
+            .enumerate()
+            .position(|(i, entry)| !served[i] && matches(entry, method, url.path(), &query))
+            .ok_or_else(|| anyhow!("No recorded response left for {} {}", method, redacted_target(url)))?;
+        served[index] = true;
+
+        let entry = &self.entries[index];
+        match &entry.response {
+            Some(response) => http_reply(response),
+            None => Err(anyhow!(
+                "{}",
+                entry.error.as_deref().unwrap_or("Recorded request failed")
+            )),
+        }
+    }
+}
+
+fn matches(entry: &RecordedEntry, method: &Method, path: &str, query: &[(String, Param)]) -> bool {
+    let request = &entry.request;
+    if !request.method.eq_ignore_ascii_case(method.as_str()) {
+        return false;
+    }
+
+    let recorded_path = Url::parse(&request.url).map(|u| u.path().to_string()).unwrap_or_else(|_| request.url.clone());
+    recorded_path == path
+        && normalized_query(request.query.iter().map(|NameValue { name, value }| (name.clone(), value.clone()))) == query
+}
+
+/// A query value; GraphQL variables are compared as JSON, so key order and
+/// spacing don't matter but `id` and the `after` cursor do
+#[derive(PartialEq)]
+enum Param {
+    Text(String),
+    Json(Value),
+}
+
+fn normalized_query(pairs: impl Iterator<Item = (String, String)>) -> Vec<(String, Param)> {
+    let mut query: Vec<(String, Param)> = pairs
+        .filter(|(name, _)| !PAGE_SIZE_PARAMS.contains(&name.as_str()))
+        .map(|(name, value)| {
+            let param = match serde_json::from_str::<Value>(&value) {
+                Ok(mut json) if name == VARIABLES_PARAM => {
+                    if let Some(variables) = json.as_object_mut() {
+                        variables.retain(|key, _| !PAGE_SIZE_PARAMS.contains(&key.as_str()));
+                    }
+                    Param::Json(json)
+                }
+                _ => Param::Text(value),
+            };
+            (name, param)
+        })
+        .collect();
+    query.sort_by(|a, b| a.0.cmp(&b.0));
+    query
+}
+
+fn redacted_target(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), redact(query)),
+        None => url.path().to_string(),
+    }
+}
+
+fn http_reply(response: &RecordedResponse) -> Result<HttpReply> {
+    let status = StatusCode::from_u16(response.status)
+        .map_err(|_| anyhow!("Invalid recorded status {}", response.status))?;
+
+    let mut headers = HeaderMap::new();
+    for NameValue { name, value } in &response.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            headers.append(name, value);
+        }
+    }
+
+    let body = match response.encoding.as_deref() {
+        Some("base64") => general_purpose::STANDARD
+            .decode(&response.body)
+            .map_err(|e| anyhow!("Invalid recorded body: {}", e))?,
+        Some(other) => return Err(anyhow!("Unknown recorded body encoding: {}", other)),
+        None => response.body.clone().into_bytes(),
+    };
+
+    Ok(HttpReply { status, headers, body })
+}
@@ -0,0 +1,170 @@
+//! # INSTAFollows Ultimate - Scan Schedule
+//!
+//! When background scans run: which accounts, how often and inside which
+//! local time window, plus the summary handed to the UI after each run.
+
+use anyhow::{anyhow, Result};
+use chrono::{Local, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::analysis::{RelationshipCounts, Relationships};
+use crate::models::Profile;
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Store setting holding the `ScheduleConfig`
+pub const SCHEDULE_SETTING: &str = "schedule";
+
+/// Store setting holding the last scheduled run per account
+pub const LAST_RUNS_SETTING: &str = "schedule_last_runs";
+
+/// Usernames listed per change type in a summary
+const SUMMARY_NAMES: usize = 20;
+
+// ============================================
+// CONFIGURATION
+// ============================================
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ScheduleConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Account IDs to scan
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u32,
+    /// Local hours scans may start in, `start..end`. Wraps past midnight;
+    /// equal hours mean any time.
+    #[serde(default = "default_window_start")]
+    pub window_start_hour: u32,
+    #[serde(default = "default_window_end")]
+    pub window_end_hour: u32,
+    /// Pacing profile for scheduled scans, the settings' one if unset
+    #[serde(default)]
+    pub pacing: Option<String>,
+}
+
+fn default_interval_hours() -> u32 {
+    24
+}
+
+fn default_window_start() -> u32 {
+    3
+}
+
+fn default_window_end() -> u32 {
+    6
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        ScheduleConfig {
+            enabled: false,
+            accounts: Vec::new(),
+            interval_hours: default_interval_hours(),
+            window_start_hour: default_window_start(),
+            window_end_hour: default_window_end(),
+            pacing: None,
+        }
+    }
+}
+
+impl ScheduleConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=24 * 30).contains(&self.interval_hours) {
+            return Err(anyhow!("Interval must be between 1 hour and 30 days"));
+        }
+        if self.window_start_hour > 23 || self.window_end_hour > 23 {
+            return Err(anyhow!("Window hours must be 0-23"));
+        }
+        if let Some(bad) = self.accounts.iter().find(|a| a.is_empty() || !a.chars().all(|c| c.is_ascii_digit())) {
+            return Err(anyhow!("Account {:?} is not a user ID", bad));
+        }
+        if self.enabled && self.accounts.is_empty() {
+            return Err(anyhow!("Add an account to schedule"));
+        }
+        Ok(())
+    }
+
+    fn in_window(&self, hour: u32) -> bool {
+        let (start, end) = (self.window_start_hour, self.window_end_hour);
+        match start.cmp(&end) {
+            Ordering::Equal => true,
+            Ordering::Less => (start..end).contains(&hour),
+            Ordering::Greater => hour >= start || hour < end,
+        }
+    }
+
+    /// Unix time the next scan of an account is due: one interval after the
+    /// last run (or now, if never run or overdue), moved forward into the
+    /// window. Runs may come up to 10% of the interval early so a daily scan
+    /// doesn't drift out of its window.
+    pub fn next_run(&self, last_run: Option<u64>, now: u64) -> u64 {
+        let interval = u64::from(self.interval_hours) * 3600;
+        let earliest = last_run.map_or(now, |last| last + interval - interval / 10).max(now);
+
+        let Some(at) = Local.timestamp_opt(earliest as i64, 0).single() else {
+            return earliest;
+        };
+        if self.in_window(at.hour()) {
+            return earliest;
+        }
+
+        // Next window start, today or tomorrow
+        let mut day = at.date_naive();
+        for _ in 0..2 {
+            let start = day
+                .and_hms_opt(self.window_start_hour, 0, 0)
+                .and_then(|t| Local.from_local_datetime(&t).earliest());
+            if let Some(start) = start.map(|s| s.timestamp() as u64).filter(|&s| s > earliest) {
+                return start;
+            }
+            day = day.succ_opt().unwrap_or(day);
+        }
+        earliest
+    }
+}
+
+// ============================================
+// SUMMARY
+// ============================================
+
+/// Result of a background scan, ready to show as a notification
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ScanSummary {
+    pub user_id: String,
+    pub taken_at: u64,
+    pub counts: RelationshipCounts,
+    /// Since the previous stored scan
+    pub gained: usize,
+    pub lost: usize,
+    /// Up to 20 usernames each
+    pub gained_usernames: Vec<String>,
+    pub lost_usernames: Vec<String>,
+}
+
+impl ScanSummary {
+    pub fn new(user_id: &str, taken_at: u64, report: &Relationships) -> Self {
+        let usernames = |profiles: &[Profile]| {
+            profiles.iter().take(SUMMARY_NAMES).map(|p| p.username.clone()).collect()
+        };
+        let (gained, lost) = match &report.changes {
+            Some(changes) => (&changes.new_followers[..], &changes.lost_followers[..]),
+            None => (&[][..], &[][..]),
+        };
+
+        ScanSummary {
+            user_id: user_id.to_string(),
+            taken_at,
+            counts: report.counts.clone(),
+            gained: gained.len(),
+            lost: lost.len(),
+            gained_usernames: usernames(gained),
+            lost_usernames: usernames(lost),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! # INSTAFollows Ultimate - Search & Sort
+//!
+//! Paginated, sorted, full-text searched views over a stored scan, so the UI
+//! only receives the window it displays instead of the full lists.
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::analysis::{fans, mutuals, non_mutuals};
+use crate::filters::Filter;
+use crate::models::{Profile, Snapshot};
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 500;
+
+// ============================================
+// QUERY TYPES
+// ============================================
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileList {
+    Followers,
+    Following,
+    Mutuals,
+    NotFollowingBack,
+    Fans,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    /// Order returned by Instagram
+    #[default]
+    Default,
+    Username,
+    FullName,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ProfileQuery {
+    /// Matched against username and full name, ignoring case and accents
+    #[serde(default)]
+    pub search: Option<String>,
+    #[serde(default)]
+    pub sort: SortKey,
+    #[serde(default)]
+    pub order: SortOrder,
+    #[serde(default)]
+    pub offset: usize,
+    /// Capped at 500
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_limit() -> usize {
+    DEFAULT_LIMIT
+}
+
+impl Default for ProfileQuery {
+    fn default() -> Self {
+        ProfileQuery {
+            search: None,
+            sort: SortKey::default(),
+            order: SortOrder::default(),
+            offset: 0,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ProfilePage {
+    pub items: Vec<Profile>,
+    /// Profiles matching the query, across all pages
+    pub total: usize,
+    pub offset: usize,
+}
+
+/// Lowercase and strip diacritics ("Jérôme" -> "jerome")
+pub fn normalize(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).flat_map(char::to_lowercase).collect()
+}
+
+// ============================================
+// PROFILE INDEX
+// ============================================
+
+/// One list with its search keys normalised up front
+pub struct ProfileIndex {
+    profiles: Vec<Profile>,
+    usernames: Vec<String>,
+    full_names: Vec<String>,
+}
+
+impl ProfileIndex {
+    pub fn new(profiles: Vec<Profile>) -> Self {
+        let usernames = profiles.iter().map(|p| normalize(&p.username)).collect();
+        let full_names = profiles.iter().map(|p| normalize(&p.full_name)).collect();
+        ProfileIndex {
+            profiles,
+            usernames,
+            full_names,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    pub fn query(&self, query: &ProfileQuery, filter: Option<&Filter>) -> ProfilePage {
+        let needle = query.search.as_deref().map(normalize).filter(|s| !s.is_empty());
+
+        // Work on indices, only the returned window gets cloned
+        let mut hits: Vec<usize> = (0..self.profiles.len())
+            .filter(|&i| match &needle {
+                Some(n) => self.usernames[i].contains(n.as_str()) || self.full_names[i].contains(n.as_str()),
+                None => true,
+            })
+            .filter(|&i| filter.is_none_or(|f| f.matches(&self.profiles[i])))
+            .collect();
+
+        match query.sort {
+            SortKey::Default => {}
+            SortKey::Username => hits.sort_by(|&a, &b| self.usernames[a].cmp(&self.usernames[b])),
+            SortKey::FullName => hits.sort_by(|&a, &b| {
+                self.full_names[a]
+                    .cmp(&self.full_names[b])
+                    .then_with(|| self.usernames[a].cmp(&self.usernames[b]))
+            }),
+        }
+        if query.order == SortOrder::Desc {
+            hits.reverse();
+        }
+
+        let limit = query.limit.min(MAX_LIMIT);
+        ProfilePage {
+            items: hits
+                .iter()
+                .skip(query.offset)
+                .take(limit)
+                .map(|&i| self.profiles[i].clone())
+                .collect(),
+            total: hits.len(),
+            offset: query.offset,
+        }
+    }
+}
+
+// ============================================
+// SCAN VIEW
+// ============================================
+
+/// Every list of a stored scan, indexed for querying
+pub struct ScanView {
+    followers: ProfileIndex,
+    following: ProfileIndex,
+    mutuals: ProfileIndex,
+    not_following_back: ProfileIndex,
+    fans: ProfileIndex,
+}
+
+impl ScanView {
+    pub fn new(snapshot: &Snapshot) -> Self {
+        ScanView {
+            followers: ProfileIndex::new(snapshot.followers.clone()),
+            following: ProfileIndex::new(snapshot.following.clone()),
+            mutuals: ProfileIndex::new(mutuals(&snapshot.followers, &snapshot.following)),
+            not_following_back: ProfileIndex::new(non_mutuals(&snapshot.followers, &snapshot.following)),
+            fans: ProfileIndex::new(fans(&snapshot.followers, &snapshot.following)),
+        }
+    }
+
+    pub fn list(&self, list: ProfileList) -> &ProfileIndex {
+        match list {
+            ProfileList::Followers => &self.followers,
+            ProfileList::Following => &self.following,
+            ProfileList::Mutuals => &self.mutuals,
+            ProfileList::NotFollowingBack => &self.not_following_back,
+            ProfileList::Fans => &self.fans,
+        }
+    }
+
+    pub fn query(&self, list: ProfileList, query: &ProfileQuery, filter: Option<&Filter>) -> ProfilePage {
+        self.list(list).query(query, filter)
+    }
+}
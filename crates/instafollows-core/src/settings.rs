@@ -0,0 +1,186 @@
+//! # INSTAFollows Ultimate - User Settings
+//!
+//! Typed, validated network settings persisted as JSON: timeouts, pacing
+//! between requests, page size, custom pacing profiles, log level and where
+//! the session copy is kept. The client reads them through a shared handle,
+//! so a change applies to the next request of a scan that is already running.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::logging::parse_level;
+use crate::pacing::{pacing_profile, PacingProfile, BUILTIN_PROFILES, STANDARD_PROFILE};
+use crate::storage::SESSION_FILE;
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Settings file name (in the working directory, or the app config dir)
+pub const SETTINGS_FILE: &str = "instafollows_settings.json";
+
+/// Pacing below this gets accounts rate limited
+pub const MIN_DELAY_FLOOR_MS: u64 = 500;
+
+/// Largest page both connection sources accept
+pub const MAX_PAGE_SIZE: usize = 100;
+
+// ============================================
+// SETTINGS
+// ============================================
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct Settings {
+    /// Whole request, connect included
+    pub request_timeout_secs: u64,
+    /// Applies when the client is built, i.e. on the next start
+    pub connect_timeout_secs: u64,
+    /// Random pause between pages in the standard pacing profile
+    pub delay_min_ms: u64,
+    pub delay_max_ms: u64,
+    /// Profiles requested per page
+    pub page_size: usize,
+    /// Internal copy of the last loaded cookies.json, if not kept in the
+    /// app's own directory
+    pub session_file: Option<PathBuf>,
+    /// Pacing profile used when a scan doesn't pick one
+    pub pacing: String,
+    /// User-defined profiles, next to the built-in ones
+    pub pacing_profiles: Vec<PacingProfile>,
+    /// "off", "error", "warn", "info", "debug" or "trace"
+    pub log_level: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            request_timeout_secs: 30,
+            connect_timeout_secs: 10,
+            delay_min_ms: 1000,
+            delay_max_ms: 2500,
+            page_size: 50,
+            session_file: None,
+            pacing: STANDARD_PROFILE.to_string(),
+            pacing_profiles: Vec::new(),
+            log_level: "info".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load(file_path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(file_path.as_ref())?;
+        let mut settings: Settings = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| anyhow!("Invalid settings file: {}", e))?;
+        // Older versions wrote their working-directory default here
+        if settings.session_file.as_deref() == Some(Path::new(SESSION_FILE)) {
+            settings.session_file = None;
+        }
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Load the settings file if present, falling back to the defaults when
+    /// it is missing or fails validation
+    pub fn load_or_default(file_path: impl AsRef<Path>) -> Self {
+        let file_path = file_path.as_ref();
+        if !file_path.exists() {
+            return Self::default();
+        }
+
+        Self::load(file_path).unwrap_or_else(|e| {
+            log::warn!("Ignoring settings file ({}), using defaults", e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, file_path: impl AsRef<Path>) -> Result<()> {
+        self.validate()?;
+        let file_path = file_path.as_ref();
+        if let Some(dir) = file_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(file_path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !(5..=300).contains(&self.request_timeout_secs) {
+            return Err(anyhow!("Request timeout must be between 5 and 300 seconds"));
+        }
+        if self.connect_timeout_secs == 0 || self.connect_timeout_secs > self.request_timeout_secs {
+            return Err(anyhow!("Connect timeout must be between 1 second and the request timeout"));
+        }
+        if self.delay_min_ms < MIN_DELAY_FLOOR_MS {
+            return Err(anyhow!("Minimum delay can't go below {} ms", MIN_DELAY_FLOOR_MS));
+        }
+        if self.delay_max_ms < self.delay_min_ms || self.delay_max_ms > 60_000 {
+            return Err(anyhow!("Maximum delay must be between the minimum delay and 60 s"));
+        }
+        if !(1..=MAX_PAGE_SIZE).contains(&self.page_size) {
+            return Err(anyhow!("Page size must be between 1 and {}", MAX_PAGE_SIZE));
+        }
+        if self.session_file.as_ref().is_some_and(|f| f.as_os_str().is_empty()) {
+            return Err(anyhow!("Session file location can't be empty"));
+        }
+        for (i, profile) in self.pacing_profiles.iter().enumerate() {
+            profile.validate()?;
+            if BUILTIN_PROFILES.contains(&profile.name.as_str())
+                || self.pacing_profiles[..i].iter().any(|p| p.name == profile.name)
+            {
+                return Err(anyhow!("Pacing profile {:?} already exists", profile.name));
+            }
+        }
+        pacing_profile(self, &self.pacing)?;
+        parse_level(&self.log_level)?;
+        Ok(())
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+
+    pub fn log_level(&self) -> log::LevelFilter {
+        parse_level(&self.log_level).unwrap_or(log::LevelFilter::Info)
+    }
+
+    /// The profile selected in the settings
+    pub fn default_pacing(&self) -> PacingProfile {
+        pacing_profile(self, &self.pacing).unwrap_or_else(|_| PacingProfile::standard(self))
+    }
+}
+
+// ============================================
+// SHARED HANDLE
+// ============================================
+
+/// Settings shared between the client and whoever edits them
+#[derive(Clone, Default)]
+pub struct SharedSettings(Arc<RwLock<Settings>>);
+
+impl SharedSettings {
+    pub fn new(settings: Settings) -> Self {
+        SharedSettings(Arc::new(RwLock::new(settings)))
+    }
+
+    pub fn get(&self) -> Settings {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the settings after validating them
+    pub fn set(&self, settings: Settings) -> Result<()> {
+        settings.validate()?;
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = settings;
+        Ok(())
+    }
+}
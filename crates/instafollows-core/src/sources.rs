@@ -0,0 +1,240 @@
+//! # INSTAFollows Ultimate - Connection Sources
+//!
+//! Independent ways of paging through a follower/following list. Each source
+//! only knows how to build a page request and parse the response; the Ghost
+//! Client drives the pagination and falls back to the next source when one
+//! answers with a payload it doesn't understand.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::fmt;
+
+use crate::endpoints::EndpointConfig;
+use crate::models::Profile;
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+pub const GRAPHQL_SOURCE: &str = "graphql";
+pub const FRIENDSHIPS_SOURCE: &str = "friendships";
+
+// ============================================
+// DATA STRUCTURES
+// ============================================
+
+/// Which side of the relationship to fetch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Followers,
+    Following,
+}
+
+impl Direction {
+    /// Stage name used in progress events
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Followers => "followers",
+            Direction::Following => "following",
+        }
+    }
+}
+
+/// A single page request, relative to the API domain
+pub struct PageRequest {
+    pub path: String,
+    pub query: Vec<(String, String)>,
+}
+
+/// A parsed page of profiles
+pub struct Page {
+    pub profiles: Vec<Profile>,
+    /// Total list size, when the source reports it
+    pub total: Option<usize>,
+    /// Cursor for the next page, `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// The endpoint answered, but not in the shape this source expects
+/// (deprecated query hash, removed field, ...). Triggers a fallback.
+#[derive(Debug)]
+pub struct SchemaError(pub String);
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unexpected response schema: {}", self.0)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+// ============================================
+// CONNECTION SOURCE TRAIT
+// ============================================
+
+pub trait ConnectionSource: Send + Sync {
+    /// Name used in the endpoints file and in logs
+    fn name(&self) -> &'static str;
+
+    /// Build the request for `page_size` profiles after `cursor` (`None` = first page)
+    fn page_request(
+        &self,
+        endpoints: &EndpointConfig,
+        user_id: &str,
+        direction: Direction,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> PageRequest;
+
+    /// Parse a page, returning a `SchemaError` if the payload is unrecognised
+    fn parse_page(&self, json: &Value, direction: Direction) -> Result<Page>;
+}
+
+/// Look up a built-in source by name
+pub fn source_by_name(name: &str) -> Option<&'static dyn ConnectionSource> {
+    match name {
+        GRAPHQL_SOURCE => Some(&GraphQlSource),
+        FRIENDSHIPS_SOURCE => Some(&FriendshipsSource),
+        _ => None,
+    }
+}
+
+// ============================================
+// GRAPHQL (legacy query_hash pagination)
+// ============================================
+
+pub struct GraphQlSource;
+
+impl ConnectionSource for GraphQlSource {
+    fn name(&self) -> &'static str {
+        GRAPHQL_SOURCE
+    }
+
+    fn page_request(
+        &self,
+        endpoints: &EndpointConfig,
+        user_id: &str,
+        direction: Direction,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> PageRequest {
+        let query_hash = match direction {
+            Direction::Followers => &endpoints.followers_hash,
+            Direction::Following => &endpoints.following_hash,
+        };
+
+        let mut variables = serde_json::json!({
+            "id": user_id,
+            "first": page_size,
+        });
+
+        if let Some(c) = cursor {
+            variables["after"] = c.into();
+        }
+
+        PageRequest {
+            path: endpoints.paths.graphql_query.clone(),
+            query: vec![
+                ("query_hash".to_string(), query_hash.clone()),
+                ("variables".to_string(), variables.to_string()),
+            ],
+        }
+    }
+
+    fn parse_page(&self, json: &Value, direction: Direction) -> Result<Page> {
+        let edge_name = match direction {
+            Direction::Followers => "edge_followed_by",
+            Direction::Following => "edge_follow",
+        };
+        let edge_data = &json["data"]["user"][edge_name];
+
+        let edges = edge_data["edges"].as_array().ok_or_else(|| {
+            SchemaError(format!(
+                "graphql: missing data.user.{}.edges ({})",
+                edge_name,
+                json["message"].as_str().unwrap_or("no message")
+            ))
+        })?;
+
+        let profiles = edges.iter().map(|edge| Profile::from_json(&edge["node"])).collect();
+
+        // Pagination
+        let page_info = &edge_data["page_info"];
+        let has_next = page_info["has_next_page"].as_bool().unwrap_or(false);
+        let next_cursor = if has_next {
+            // Stopping here would pass a partial list off as complete
+            let cursor = page_info["end_cursor"]
+                .as_str()
+                .ok_or_else(|| SchemaError(format!("graphql: next page of {} has no end_cursor", edge_name)))?;
+            Some(cursor.to_string())
+        } else {
+            None
+        };
+
+        Ok(Page {
+            profiles,
+            total: edge_data["count"].as_u64().map(|c| c as usize),
+            next_cursor,
+        })
+    }
+}
+
+// ============================================
+// FRIENDSHIPS API (max_id pagination)
+// ============================================
+
+pub struct FriendshipsSource;
+
+impl ConnectionSource for FriendshipsSource {
+    fn name(&self) -> &'static str {
+        FRIENDSHIPS_SOURCE
+    }
+
+    fn page_request(
+        &self,
+        endpoints: &EndpointConfig,
+        user_id: &str,
+        direction: Direction,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> PageRequest {
+        let path = match direction {
+            Direction::Followers => &endpoints.paths.friendships_followers,
+            Direction::Following => &endpoints.paths.friendships_following,
+        };
+
+        let mut query = vec![("count".to_string(), page_size.to_string())];
+        if let Some(c) = cursor {
+            query.push(("max_id".to_string(), c.to_string()));
+        }
+
+        PageRequest {
+            path: path.replace("{user_id}", user_id),
+            query,
+        }
+    }
+
+    fn parse_page(&self, json: &Value, _direction: Direction) -> Result<Page> {
+        let users = json["users"].as_array().ok_or_else(|| {
+            SchemaError(format!(
+                "friendships: missing users ({})",
+                json["message"].as_str().unwrap_or("no message")
+            ))
+        })?;
+
+        let profiles = users.iter().map(Profile::from_json).collect();
+
+        // next_max_id is a string on most accounts, a number on some
+        let next_cursor = match &json["next_max_id"] {
+            Value::String(s) if !s.is_empty() => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        };
+
+        Ok(Page {
+            profiles,
+            total: None,
+            next_cursor,
+        })
+    }
+}
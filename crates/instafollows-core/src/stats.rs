@@ -0,0 +1,355 @@
+//! # INSTAFollows Ultimate - Growth Statistics
+//!
+//! Trends over a series of snapshots of one account: counts, gains and
+//! losses, churn, follow-back ratio and how long followers stay, shaped as
+//! time series for charting, plus per-follower tenure and follow-for-follow
+//! detection.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::analysis::{diff, keyed_by_id, mutuals, profile_key, unique_count};
+use crate::models::{Profile, Snapshot};
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+const DAY_SECS: u64 = 86_400;
+
+// ============================================
+// DATA STRUCTURES
+// ============================================
+
+/// One snapshot's numbers
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct StatsPoint {
+    /// Unix timestamp (seconds)
+    pub taken_at: u64,
+    pub followers: usize,
+    pub following: usize,
+    pub mutuals: usize,
+    /// Share of followed accounts that follow back (0.0 - 1.0)
+    pub mutual_ratio: f64,
+    /// Since the previous snapshot, `None` for the first one
+    pub gained_followers: Option<usize>,
+    pub lost_followers: Option<usize>,
+    /// Lost followers over the previous follower count
+    pub churn_rate: Option<f64>,
+}
+
+/// Gains and losses by UTC day. Changes between two snapshots count on the
+/// day of the later one.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DailyPoint {
+    /// Unix timestamp of the day's midnight (UTC)
+    pub day: u64,
+    pub gained: usize,
+    pub lost: usize,
+    pub net: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct GrowthStats {
+    pub points: Vec<StatsPoint>,
+    pub daily: Vec<DailyPoint>,
+    /// Mean time followers stayed, ongoing follows counted up to the latest
+    /// snapshot. `None` with fewer than two snapshots.
+    pub average_follow_days: Option<f64>,
+}
+
+/// An unbroken stretch of snapshots in which a profile followed the account
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FollowSpan {
+    /// As seen in the last snapshot of the span
+    pub profile: Profile,
+    pub first_seen: u64,
+    pub last_seen: u64,
+    /// Still following in the latest snapshot
+    pub ongoing: bool,
+}
+
+impl FollowSpan {
+    pub fn days(&self) -> f64 {
+        self.last_seen.saturating_sub(self.first_seen) as f64 / DAY_SECS as f64
+    }
+}
+
+/// A current follower and how long they've been following
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FollowerTenure {
+    pub profile: Profile,
+    /// First snapshot of the current stay
+    pub following_since: u64,
+    /// Up to the latest snapshot
+    pub days: f64,
+    /// Already following in the oldest snapshot, so the real tenure is longer
+    pub predates_history: bool,
+}
+
+/// A follower who left, with the first and last snapshot they followed in
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DepartedFollower {
+    pub profile: Profile,
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub days: f64,
+    pub predates_history: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FollowDurations {
+    /// Longest-standing first
+    pub current: Vec<FollowerTenure>,
+    /// Most recent departures first; someone who left twice appears twice
+    pub departed: Vec<DepartedFollower>,
+}
+
+/// What counts as follow-for-follow behaviour
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct QuickUnfollowOptions {
+    /// A stay this short (or shorter) that ended is a quick unfollow
+    #[serde(default = "default_window_days")]
+    pub window_days: f64,
+    /// This many ended stays is a repeated follow/unfollow
+    #[serde(default = "default_min_cycles")]
+    pub min_cycles: usize,
+}
+
+fn default_window_days() -> f64 {
+    7.0
+}
+
+fn default_min_cycles() -> usize {
+    2
+}
+
+impl Default for QuickUnfollowOptions {
+    fn default() -> Self {
+        QuickUnfollowOptions {
+            window_days: default_window_days(),
+            min_cycles: default_min_cycles(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Stay {
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub days: f64,
+    pub ended: bool,
+}
+
+/// An account that followed and unfollowed quickly or repeatedly
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FollowCycler {
+    /// As last seen
+    pub profile: Profile,
+    /// Every stay, oldest first
+    pub stays: Vec<Stay>,
+    /// Followed after the oldest snapshot and left within the window
+    pub quick: bool,
+    /// Left at least `min_cycles` times
+    pub repeated: bool,
+    pub following_now: bool,
+}
+
+// ============================================
+// ANALYTICS
+// ============================================
+
+/// Statistics over snapshots of one account, oldest first
+pub fn growth_stats(snapshots: &[Snapshot]) -> GrowthStats {
+    let mut points = Vec::with_capacity(snapshots.len());
+    let mut daily: BTreeMap<u64, (usize, usize)> = BTreeMap::new();
+
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        // Counted like `relationships`, so an account repeated by a shifting
+        // page isn't counted twice
+        let keyed = keyed_by_id(&[&snapshot.followers, &snapshot.following]);
+        let followers = unique_count(&snapshot.followers, keyed);
+        let following = unique_count(&snapshot.following, keyed);
+        let mutuals = mutuals(&snapshot.followers, &snapshot.following).len();
+        let changes = i.checked_sub(1).map(|prev| diff(&snapshots[prev], snapshot));
+        let previous_followers = points.last().map_or(0, |p: &StatsPoint| p.followers);
+
+        if let Some(changes) = &changes {
+            let day = daily.entry(snapshot.taken_at / DAY_SECS * DAY_SECS).or_default();
+            day.0 += changes.new_followers.len();
+            day.1 += changes.lost_followers.len();
+        }
+
+        points.push(StatsPoint {
+            taken_at: snapshot.taken_at,
+            followers,
+            following,
+            mutuals,
+            mutual_ratio: ratio(mutuals, following),
+            gained_followers: changes.as_ref().map(|c| c.new_followers.len()),
+            lost_followers: changes.as_ref().map(|c| c.lost_followers.len()),
+            churn_rate: changes.as_ref().map(|c| ratio(c.lost_followers.len(), previous_followers)),
+        });
+    }
+
+    let spans = follower_spans(snapshots);
+    let average_follow_days = (snapshots.len() > 1 && !spans.is_empty())
+        .then(|| spans.iter().map(FollowSpan::days).sum::<f64>() / spans.len() as f64);
+
+    GrowthStats {
+        points,
+        daily: daily
+            .into_iter()
+            .map(|(day, (gained, lost))| DailyPoint {
+                day,
+                gained,
+                lost,
+                net: gained as i64 - lost as i64,
+            })
+            .collect(),
+        average_follow_days,
+    }
+}
+
+/// Every follow span across snapshots of one account, oldest first. Someone
+/// who left and came back has one span per stay. Snapshots may come in any
+/// order; they are walked by `taken_at`.
+pub fn follower_spans(snapshots: &[Snapshot]) -> Vec<FollowSpan> {
+    let lists: Vec<&[Profile]> = snapshots.iter().map(|s| s.followers.as_slice()).collect();
+    let keyed = keyed_by_id(&lists);
+    let mut ordered: Vec<&Snapshot> = snapshots.iter().collect();
+    ordered.sort_by_key(|s| s.taken_at);
+
+    let mut open: HashMap<&str, FollowSpan> = HashMap::new();
+    let mut spans = Vec::new();
+
+    for snapshot in ordered {
+        let mut still_open = HashMap::with_capacity(snapshot.followers.len());
+        for profile in &snapshot.followers {
+            let key = profile_key(profile, keyed);
+            // Listed twice by a shifting page: the first entry carried the span over
+            if still_open.contains_key(key) {
+                continue;
+            }
+            let span = match open.remove(key) {
+                Some(span) => FollowSpan {
+                    profile: profile.clone(),
+                    last_seen: snapshot.taken_at,
+                    ..span
+                },
+                None => FollowSpan {
+                    profile: profile.clone(),
+                    first_seen: snapshot.taken_at,
+                    last_seen: snapshot.taken_at,
+                    ongoing: true,
+                },
+            };
+            still_open.insert(key, span);
+        }
+
+        // Whoever is left in `open` wasn't in this snapshot
+        spans.extend(open.into_values().map(|span| FollowSpan { ongoing: false, ..span }));
+        open = still_open;
+    }
+
+    spans.extend(open.into_values());
+    spans.sort_by(|a, b| a.first_seen.cmp(&b.first_seen).then(a.profile.username.cmp(&b.profile.username)));
+    spans
+}
+
+/// Tenure of every current follower and the stay of everyone who left,
+/// over snapshots of one account
+pub fn follow_durations(snapshots: &[Snapshot]) -> FollowDurations {
+    let oldest = snapshots.iter().map(|s| s.taken_at).min().unwrap_or(0);
+
+    let mut durations = FollowDurations {
+        current: Vec::new(),
+        departed: Vec::new(),
+    };
+    for span in follower_spans(snapshots) {
+        let days = span.days();
+        let predates_history = span.first_seen == oldest;
+        if span.ongoing {
+            durations.current.push(FollowerTenure {
+                profile: span.profile,
+                following_since: span.first_seen,
+                days,
+                predates_history,
+            });
+        } else {
+            durations.departed.push(DepartedFollower {
+                profile: span.profile,
+                first_seen: span.first_seen,
+                last_seen: span.last_seen,
+                days,
+                predates_history,
+            });
+        }
+    }
+
+    durations.current.sort_by_key(|t| t.following_since);
+    durations.departed.sort_by_key(|d| Reverse(d.last_seen));
+    durations
+}
+
+/// Accounts that unfollowed within `window_days` of following, or followed
+/// and unfollowed repeatedly, over snapshots of one account. Most recent
+/// departure first.
+pub fn quick_unfollowers(snapshots: &[Snapshot], options: &QuickUnfollowOptions) -> Vec<FollowCycler> {
+    let oldest = snapshots.iter().map(|s| s.taken_at).min().unwrap_or(0);
+    let lists: Vec<&[Profile]> = snapshots.iter().map(|s| s.followers.as_slice()).collect();
+    let keyed = keyed_by_id(&lists);
+
+    // Spans come oldest first, so each account's stays do too
+    let mut by_account: HashMap<String, Vec<FollowSpan>> = HashMap::new();
+    for span in follower_spans(snapshots) {
+        by_account
+            .entry(profile_key(&span.profile, keyed).to_string())
+            .or_default()
+            .push(span);
+    }
+
+    let mut cyclers: Vec<FollowCycler> = by_account
+        .into_values()
+        .filter_map(|spans| {
+            let ended: Vec<&FollowSpan> = spans.iter().filter(|s| !s.ongoing).collect();
+            let quick = ended
+                .iter()
+                .any(|s| s.first_seen != oldest && s.days() <= options.window_days);
+            let repeated = ended.len() >= options.min_cycles.max(1);
+            if !quick && !repeated {
+                return None;
+            }
+
+            let last = spans.last()?;
+            Some(FollowCycler {
+                profile: last.profile.clone(),
+                following_now: last.ongoing,
+                stays: spans
+                    .iter()
+                    .map(|s| Stay {
+                        first_seen: s.first_seen,
+                        last_seen: s.last_seen,
+                        days: s.days(),
+                        ended: !s.ongoing,
+                    })
+                    .collect(),
+                quick,
+                repeated,
+            })
+        })
+        .collect();
+
+    let last_departure = |c: &FollowCycler| c.stays.iter().filter(|s| s.ended).map(|s| s.last_seen).max();
+    cyclers.sort_by_key(|c| Reverse(last_departure(c)));
+    cyclers
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
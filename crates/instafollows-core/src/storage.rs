@@ -0,0 +1,96 @@
+//! # INSTAFollows Ultimate - Storage
+//!
+//! File persistence: the saved browser session, snapshot files and saved
+//! filter rule sets.
+
+use anyhow::{anyhow, Result};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use crate::filters::RuleSet;
+use crate::models::{CookieItem, Snapshot};
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Internal copy of the last loaded cookies.json
+pub const SESSION_FILE: &str = "instafollows_session.json";
+
+/// Saved, named filter rule sets
+pub const RULES_FILE: &str = "instafollows_rules.json";
+
+// ============================================
+// SESSION
+// ============================================
+
+/// Read a cookies.json exported from the browser
+pub fn read_cookies(file_path: impl AsRef<Path>) -> Result<Vec<CookieItem>> {
+    let file = File::open(file_path.as_ref())?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+pub fn write_cookies(file_path: impl AsRef<Path>, cookies: &[CookieItem]) -> Result<()> {
+    std::fs::write(file_path.as_ref(), serde_json::to_string(cookies)?)?;
+    Ok(())
+}
+
+// ============================================
+// SNAPSHOTS
+// ============================================
+
+impl Snapshot {
+    pub fn load(file_path: impl AsRef<Path>) -> Result<Self> {
+        let file_path = file_path.as_ref();
+        let file = File::open(file_path)
+            .map_err(|e| anyhow!("Cannot open snapshot {}: {}", file_path.display(), e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| anyhow!("Invalid snapshot {}: {}", file_path.display(), e))
+    }
+
+    pub fn save(&self, file_path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(file_path.as_ref())?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+}
+
+// ============================================
+// RULE SETS
+// ============================================
+
+/// Read rule sets from a file holding either one set or a list of them
+/// (a shared set or a whole saved collection). A missing file is empty.
+pub fn read_rule_sets(file_path: impl AsRef<Path>) -> Result<Vec<RuleSet>> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(RuleSet),
+        Many(Vec<RuleSet>),
+    }
+
+    let file_path = file_path.as_ref();
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file = File::open(file_path)?;
+    let sets = match serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| anyhow!("Invalid rules file {}: {}", file_path.display(), e))?
+    {
+        OneOrMany::One(set) => vec![set],
+        OneOrMany::Many(sets) => sets,
+    };
+
+    for set in &sets {
+        set.compile()?;
+    }
+    Ok(sets)
+}
+
+pub fn write_rule_sets(file_path: impl AsRef<Path>, sets: &[RuleSet]) -> Result<()> {
+    let file = File::create(file_path.as_ref())?;
+    serde_json::to_writer_pretty(BufWriter::new(file), sets)?;
+    Ok(())
+}
@@ -0,0 +1,631 @@
+//! # INSTAFollows Ultimate - Local Store
+//!
+//! Embedded SQLite database behind history, diffs, search and the action log:
+//! profiles keyed by ID with their rename history, the membership of every
+//! snapshot, unfollow actions, the request ledger and key/value settings.
+//! The schema is upgraded in place on open.
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::budget::RequestKind;
+use crate::models::{unix_now, Profile, Snapshot, SnapshotSource};
+
+// ============================================
+// CONSTANTS
+// ============================================
+
+/// Local database, next to the session file
+pub const DATABASE_FILE: &str = "instafollows.db";
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many have run; never edit one that has shipped, append a new one.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE profiles (
+        id TEXT PRIMARY KEY,
+        username TEXT NOT NULL,
+        full_name TEXT NOT NULL,
+        profile_pic_url TEXT NOT NULL,
+        profile_pic_url_hd TEXT,
+        is_verified INTEGER NOT NULL,
+        is_private INTEGER NOT NULL,
+        is_business_account INTEGER NOT NULL,
+        is_professional_account INTEGER NOT NULL,
+        category_name TEXT,
+        first_seen INTEGER NOT NULL,
+        last_seen INTEGER NOT NULL
+    );
+    CREATE INDEX profiles_username ON profiles (username COLLATE NOCASE);
+
+    CREATE TABLE snapshots (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        account_id TEXT NOT NULL,
+        taken_at INTEGER NOT NULL,
+        source TEXT NOT NULL
+    );
+    CREATE INDEX snapshots_account ON snapshots (account_id, taken_at);
+
+    -- Names are kept as they were at snapshot time; profile_id is NULL for
+    -- archive entries whose username was never seen in a scan
+    CREATE TABLE snapshot_members (
+        snapshot_id INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        list TEXT NOT NULL CHECK (list IN ('followers', 'following')),
+        position INTEGER NOT NULL,
+        profile_id TEXT REFERENCES profiles (id),
+        username TEXT NOT NULL,
+        full_name TEXT NOT NULL,
+        PRIMARY KEY (snapshot_id, list, position)
+    );
+    CREATE INDEX snapshot_members_profile ON snapshot_members (profile_id);
+
+    CREATE TABLE actions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        account_id TEXT,
+        kind TEXT NOT NULL,
+        target_id TEXT NOT NULL,
+        target_username TEXT,
+        performed_at INTEGER NOT NULL,
+        success INTEGER NOT NULL,
+        error TEXT
+    );
+    CREATE INDEX actions_account ON actions (account_id, performed_at);
+
+    CREATE TABLE settings (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );",
+    // 2: username / display name history
+    "CREATE TABLE profile_changes (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        profile_id TEXT NOT NULL REFERENCES profiles (id),
+        field TEXT NOT NULL CHECK (field IN ('username', 'full_name')),
+        old_value TEXT NOT NULL,
+        new_value TEXT NOT NULL,
+        detected_at INTEGER NOT NULL,
+        snapshot_id INTEGER REFERENCES snapshots (id) ON DELETE SET NULL
+    );
+    CREATE INDEX profile_changes_profile ON profile_changes (profile_id, detected_at);",
+    // 3: request ledger
+    "CREATE TABLE requests (
+        account_id TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        at INTEGER NOT NULL
+    );
+    CREATE INDEX requests_window ON requests (account_id, kind, at);
+    CREATE INDEX requests_at ON requests (at);",
+];
+
+// ============================================
+// DATA STRUCTURES
+// ============================================
+
+/// A stored snapshot without its lists
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SnapshotInfo {
+    pub id: i64,
+    pub account_id: String,
+    pub taken_at: u64,
+    pub source: SnapshotSource,
+    pub followers: usize,
+    pub following: usize,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileField {
+    Username,
+    FullName,
+}
+
+/// A rename seen between two scans of the same profile ID
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ProfileChange {
+    pub profile_id: String,
+    pub field: ProfileField,
+    pub old_value: String,
+    pub new_value: String,
+    /// Unix timestamp (seconds) of the snapshot that revealed it
+    pub detected_at: u64,
+    pub snapshot_id: Option<i64>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    Unfollow,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ActionRecord {
+    pub id: i64,
+    /// Account that performed the action, if known
+    pub account_id: Option<String>,
+    pub kind: ActionKind,
+    pub target_id: String,
+    pub target_username: Option<String>,
+    /// Unix timestamp (seconds)
+    pub performed_at: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+// ============================================
+// STORE
+// ============================================
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Open (or create) the database and bring its schema up to date
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .map_err(|e| anyhow!("Cannot open database {}: {}", path.display(), e))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::init(conn)
+    }
+
+    /// Throwaway database, for tools and tests
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > MIGRATIONS.len() {
+            return Err(anyhow!(
+                "Database schema v{} is newer than this build supports (v{})",
+                version,
+                MIGRATIONS.len()
+            ));
+        }
+
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration)
+                .map_err(|e| anyhow!("Migration {} failed: {}", i + 1, e))?;
+            tx.pragma_update(None, "user_version", i + 1)?;
+            tx.commit()?;
+            log::info!("Database migrated to schema v{}", i + 1);
+        }
+
+        Ok(Store { conn })
+    }
+
+    // ----------------------------------------
+    // Snapshots
+    // ----------------------------------------
+
+    /// Store a snapshot, updating every profile it contains and recording
+    /// any rename. Profiles keep the details of the newest snapshot they
+    /// appear in, whatever order snapshots are saved in. Entries without an
+    /// ID (archive imports) get the ID last seen for their username, so they
+    /// diff against scans by ID.
+    pub fn save_snapshot(&mut self, snapshot: &Snapshot) -> Result<i64> {
+        let tx = self.conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO snapshots (account_id, taken_at, source) VALUES (?1, ?2, ?3)",
+            params![snapshot.user_id, snapshot.taken_at, source_str(snapshot.source)],
+        )?;
+        let snapshot_id = tx.last_insert_rowid();
+
+        {
+            let mut upsert = tx.prepare(
+                "INSERT INTO profiles (id, username, full_name, profile_pic_url, profile_pic_url_hd,
+                    is_verified, is_private, is_business_account, is_professional_account,
+                    category_name, first_seen, last_seen)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11)
+                 ON CONFLICT (id) DO UPDATE SET
+                    username = CASE WHEN excluded.last_seen >= last_seen
+                        THEN excluded.username ELSE username END,
+                    full_name = CASE WHEN excluded.last_seen >= last_seen
+                        THEN excluded.full_name ELSE full_name END,
+                    profile_pic_url = CASE WHEN excluded.last_seen >= last_seen
+                        THEN excluded.profile_pic_url ELSE profile_pic_url END,
+                    profile_pic_url_hd = CASE WHEN excluded.last_seen >= last_seen
+                        THEN excluded.profile_pic_url_hd ELSE profile_pic_url_hd END,
+                    is_verified = CASE WHEN excluded.last_seen >= last_seen
+                        THEN excluded.is_verified ELSE is_verified END,
+                    is_private = CASE WHEN excluded.last_seen >= last_seen
+                        THEN excluded.is_private ELSE is_private END,
+                    is_business_account = CASE WHEN excluded.last_seen >= last_seen
+                        THEN excluded.is_business_account ELSE is_business_account END,
+                    is_professional_account = CASE WHEN excluded.last_seen >= last_seen
+                        THEN excluded.is_professional_account ELSE is_professional_account END,
+                    category_name = CASE WHEN excluded.last_seen >= last_seen
+                        THEN excluded.category_name ELSE category_name END,
+                    first_seen = MIN(first_seen, excluded.first_seen),
+                    last_seen = MAX(last_seen, excluded.last_seen)",
+            )?;
+            let mut resolve = tx.prepare("SELECT id FROM profiles WHERE username = ?1 COLLATE NOCASE")?;
+            let mut known = tx.prepare("SELECT username, full_name, last_seen FROM profiles WHERE id = ?1")?;
+            let mut change = tx.prepare(
+                "INSERT INTO profile_changes (profile_id, field, old_value, new_value, detected_at, snapshot_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            let mut member = tx.prepare(
+                "INSERT INTO snapshot_members (snapshot_id, list, position, profile_id, username, full_name)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+
+            // Profiles in both lists are updated once, from their first entry
+            let mut updated = HashSet::new();
+            for (list, profiles) in [("followers", &snapshot.followers), ("following", &snapshot.following)] {
+                for (position, p) in profiles.iter().enumerate() {
+                    let profile_id = if p.id.is_empty() {
+                        resolve.query_row([&p.username], |row| row.get::<_, String>(0)).optional()?
+                    } else if !updated.insert(p.id.as_str()) {
+                        Some(p.id.clone())
+                    } else {
+                        let previous = known
+                            .query_row([&p.id], |row| {
+                                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, u64>(2)?))
+                            })
+                            .optional()?;
+                        // An older snapshot (a late archive import) can't rename
+                        // anyone: the stored names are newer than its own
+                        if let Some((username, full_name, _)) =
+                            previous.filter(|(_, _, last_seen)| snapshot.taken_at >= *last_seen)
+                        {
+                            for (field, old, new) in [
+                                (ProfileField::Username, username, &p.username),
+                                (ProfileField::FullName, full_name, &p.full_name),
+                            ] {
+                                if &old != new {
+                                    change.execute(params![
+                                        p.id,
+                                        field_str(field),
+                                        old,
+                                        new,
+                                        snapshot.taken_at,
+                                        snapshot_id
+                                    ])?;
+                                }
+                            }
+                        }
+
+                        upsert.execute(params![
+                            p.id,
+                            p.username,
+                            p.full_name,
+                            p.profile_pic_url,
+                            p.profile_pic_url_hd,
+                            p.is_verified,
+                            p.is_private,
+                            p.is_business_account,
+                            p.is_professional_account,
+                            p.category_name,
+                            snapshot.taken_at,
+                        ])?;
+                        Some(p.id.clone())
+                    };
+                    member.execute(params![snapshot_id, list, position, profile_id, p.username, p.full_name])?;
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(snapshot_id)
+    }
+
+    /// Stored snapshots of an account, newest first
+    pub fn list_snapshots(&self, account_id: &str) -> Result<Vec<SnapshotInfo>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.account_id, s.taken_at, s.source,
+                (SELECT COUNT(*) FROM snapshot_members m WHERE m.snapshot_id = s.id AND m.list = 'followers'),
+                (SELECT COUNT(*) FROM snapshot_members m WHERE m.snapshot_id = s.id AND m.list = 'following')
+             FROM snapshots s WHERE s.account_id = ?1
+             ORDER BY s.taken_at DESC, s.id DESC",
+        )?;
+        let rows = stmt.query_map([account_id], |row| {
+            Ok(SnapshotInfo {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                taken_at: row.get(2)?,
+                source: parse_source(&row.get::<_, String>(3)?),
+                followers: row.get(4)?,
+                following: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn snapshot(&self, snapshot_id: i64) -> Result<Option<Snapshot>> {
+        let header = self
+            .conn
+            .query_row(
+                "SELECT account_id, taken_at, source FROM snapshots WHERE id = ?1",
+                [snapshot_id],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?, row.get::<_, String>(2)?)),
+            )
+            .optional()?;
+        let Some((user_id, taken_at, source)) = header else {
+            return Ok(None);
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT m.list, m.profile_id, m.username, m.full_name, p.profile_pic_url, p.profile_pic_url_hd,
+                p.is_verified, p.is_private, p.is_business_account, p.is_professional_account, p.category_name
+             FROM snapshot_members m LEFT JOIN profiles p ON p.id = m.profile_id
+             WHERE m.snapshot_id = ?1
+             ORDER BY m.list, m.position",
+        )?;
+        let mut rows = stmt.query([snapshot_id])?;
+
+        let mut snapshot = Snapshot {
+            user_id,
+            taken_at,
+            source: parse_source(&source),
+            followers: Vec::new(),
+            following: Vec::new(),
+        };
+        while let Some(row) = rows.next()? {
+            let profile = Profile {
+                id: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                username: row.get(2)?,
+                full_name: row.get(3)?,
+                profile_pic_url: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                profile_pic_url_hd: row.get(5)?,
+                is_verified: row.get::<_, Option<bool>>(6)?.unwrap_or(false),
+                is_private: row.get::<_, Option<bool>>(7)?.unwrap_or(false),
+                is_business_account: row.get::<_, Option<bool>>(8)?.unwrap_or(false),
+                is_professional_account: row.get::<_, Option<bool>>(9)?.unwrap_or(false),
+                category_name: row.get(10)?,
+            };
+            match row.get::<_, String>(0)?.as_str() {
+                "followers" => snapshot.followers.push(profile),
+                _ => snapshot.following.push(profile),
+            }
+        }
+
+        Ok(Some(snapshot))
+    }
+
+    /// Every stored snapshot of an account taken at or after `since`,
+    /// oldest first
+    pub fn snapshots(&self, account_id: &str, since: Option<u64>) -> Result<Vec<Snapshot>> {
+        let ids = {
+            let mut stmt = self.conn.prepare(
+                "SELECT id FROM snapshots WHERE account_id = ?1 AND taken_at >= ?2 ORDER BY taken_at, id",
+            )?;
+            let rows = stmt.query_map(params![account_id, since.unwrap_or(0)], |row| row.get::<_, i64>(0))?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        ids.into_iter()
+            .filter_map(|id| self.snapshot(id).transpose())
+            .collect()
+    }
+
+    /// Most recent snapshot of an account
+    pub fn latest_snapshot(&self, account_id: &str) -> Result<Option<Snapshot>> {
+        let id = self
+            .conn
+            .query_row(
+                "SELECT id FROM snapshots WHERE account_id = ?1 ORDER BY taken_at DESC, id DESC LIMIT 1",
+                [account_id],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+        match id {
+            Some(id) => self.snapshot(id),
+            None => Ok(None),
+        }
+    }
+
+    // ----------------------------------------
+    // Profiles
+    // ----------------------------------------
+
+    /// Latest known state of a profile
+    pub fn profile(&self, profile_id: &str) -> Result<Option<Profile>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT id, username, full_name, profile_pic_url, profile_pic_url_hd, is_verified,
+                    is_private, is_business_account, is_professional_account, category_name
+                 FROM profiles WHERE id = ?1",
+                [profile_id],
+                |row| {
+                    Ok(Profile {
+                        id: row.get(0)?,
+                        username: row.get(1)?,
+                        full_name: row.get(2)?,
+                        profile_pic_url: row.get(3)?,
+                        profile_pic_url_hd: row.get(4)?,
+                        is_verified: row.get(5)?,
+                        is_private: row.get(6)?,
+                        is_business_account: row.get(7)?,
+                        is_professional_account: row.get(8)?,
+                        category_name: row.get(9)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Every recorded rename of a profile, oldest first
+    pub fn profile_history(&self, profile_id: &str) -> Result<Vec<ProfileChange>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT profile_id, field, old_value, new_value, detected_at, snapshot_id
+             FROM profile_changes WHERE profile_id = ?1
+             ORDER BY detected_at, id",
+        )?;
+        let rows = stmt.query_map([profile_id], |row| {
+            Ok(ProfileChange {
+                profile_id: row.get(0)?,
+                field: parse_field(&row.get::<_, String>(1)?),
+                old_value: row.get(2)?,
+                new_value: row.get(3)?,
+                detected_at: row.get(4)?,
+                snapshot_id: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    // ----------------------------------------
+    // Actions
+    // ----------------------------------------
+
+    pub fn record_action(
+        &self,
+        account_id: Option<&str>,
+        kind: ActionKind,
+        target_id: &str,
+        target_username: Option<&str>,
+        outcome: std::result::Result<(), &str>,
+    ) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO actions (account_id, kind, target_id, target_username, performed_at, success, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                account_id,
+                action_str(kind),
+                target_id,
+                target_username,
+                unix_now(),
+                outcome.is_ok(),
+                outcome.err(),
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Action log, newest first (`None` = every account)
+    pub fn actions(&self, account_id: Option<&str>, limit: usize) -> Result<Vec<ActionRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, account_id, kind, target_id, target_username, performed_at, success, error
+             FROM actions WHERE ?1 IS NULL OR account_id = ?1
+             ORDER BY performed_at DESC, id DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![account_id, limit], |row| {
+            Ok(ActionRecord {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                kind: parse_action(&row.get::<_, String>(2)?),
+                target_id: row.get(3)?,
+                target_username: row.get(4)?,
+                performed_at: row.get(5)?,
+                success: row.get(6)?,
+                error: row.get(7)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    // ----------------------------------------
+    // Request ledger
+    // ----------------------------------------
+
+    pub fn record_request(&self, account_id: &str, kind: RequestKind, at: u64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO requests (account_id, kind, at) VALUES (?1, ?2, ?3)",
+            params![account_id, kind.as_str(), at],
+        )?;
+        Ok(())
+    }
+
+    /// Requests of one kind at or after `since`, with the oldest timestamp
+    pub fn requests_since(&self, account_id: &str, kind: RequestKind, since: u64) -> Result<(u32, Option<u64>)> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*), MIN(at) FROM requests WHERE account_id = ?1 AND kind = ?2 AND at >= ?3",
+            params![account_id, kind.as_str(), since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?)
+    }
+
+    /// Timestamps of requests of one kind at or after `since`, oldest first
+    pub fn request_times(&self, account_id: &str, kind: RequestKind, since: u64) -> Result<Vec<u64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT at FROM requests WHERE account_id = ?1 AND kind = ?2 AND at >= ?3 ORDER BY at")?;
+        let rows = stmt.query_map(params![account_id, kind.as_str(), since], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn prune_requests(&self, before: u64) -> Result<()> {
+        self.conn.execute("DELETE FROM requests WHERE at < ?1", [before])?;
+        Ok(())
+    }
+
+    // ----------------------------------------
+    // Settings
+    // ----------------------------------------
+
+    /// Setting stored as JSON, `None` if unset
+    pub fn setting<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let value = self
+            .conn
+            .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get::<_, String>(0))
+            .optional()?;
+        value
+            .map(|v| serde_json::from_str(&v).map_err(|e| anyhow!("Invalid setting {}: {}", key, e)))
+            .transpose()
+    }
+
+    pub fn set_setting<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![key, serde_json::to_string(value)?],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_setting(&self, key: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM settings WHERE key = ?1", [key])?;
+        Ok(())
+    }
+}
+
+// ============================================
+// COLUMN ENCODING
+// ============================================
+
+fn source_str(source: SnapshotSource) -> &'static str {
+    match source {
+        SnapshotSource::Scan => "scan",
+        SnapshotSource::Archive => "archive",
+    }
+}
+
+fn parse_source(value: &str) -> SnapshotSource {
+    match value {
+        "archive" => SnapshotSource::Archive,
+        _ => SnapshotSource::Scan,
+    }
+}
+
+fn field_str(field: ProfileField) -> &'static str {
+    match field {
+        ProfileField::Username => "username",
+        ProfileField::FullName => "full_name",
+    }
+}
+
+fn parse_field(value: &str) -> ProfileField {
+    match value {
+        "full_name" => ProfileField::FullName,
+        _ => ProfileField::Username,
+    }
+}
+
+fn action_str(kind: ActionKind) -> &'static str {
+    match kind {
+        ActionKind::Unfollow => "unfollow",
+    }
+}
+
+fn parse_action(_value: &str) -> ActionKind {
+    ActionKind::Unfollow
+}
@@ -0,0 +1,182 @@
+//! Properties of the relationship set algebra, on lists that overlap, repeat
+//! accounts and come in any order
+
+use instafollows_core::analysis::{diff, fans, mutuals, non_mutuals, relationships};
+use instafollows_core::models::SnapshotSource;
+use instafollows_core::stats::growth_stats;
+use instafollows_core::{Profile, Snapshot};
+use proptest::prelude::*;
+use proptest::sample::Index;
+use serde_json::json;
+use std::collections::BTreeSet;
+
+/// An account from a small pool so lists overlap; archive profiles only
+/// carry a username
+fn profile(n: u8, with_id: bool) -> Profile {
+    if with_id {
+        Profile::from_json(&json!({ "id": n.to_string(), "username": format!("user{}", n) }))
+    } else {
+        Profile::from_json(&json!({ "username": format!("user{}", n) }))
+    }
+}
+
+fn list(with_id: bool) -> impl Strategy<Value = Vec<Profile>> {
+    prop::collection::vec(0u8..40, 0..60).prop_map(move |ns| ns.into_iter().map(|n| profile(n, with_id)).collect())
+}
+
+/// Followers and following, both from a scan or both from an archive
+fn lists() -> impl Strategy<Value = (Vec<Profile>, Vec<Profile>)> {
+    any::<bool>().prop_flat_map(|with_id| (list(with_id), list(with_id)))
+}
+
+/// A list next to the same accounts shuffled, some repeated as a page that
+/// shifted would repeat them
+fn with_reshuffled(with_id: bool) -> impl Strategy<Value = (Vec<Profile>, Vec<Profile>)> {
+    (list(with_id), prop::collection::vec(any::<Index>(), 0..10)).prop_flat_map(|(profiles, repeats)| {
+        let mut reshuffled = profiles.clone();
+        if !profiles.is_empty() {
+            reshuffled.extend(repeats.iter().map(|i| i.get(&profiles).clone()));
+        }
+        (Just(profiles), Just(reshuffled).prop_shuffle())
+    })
+}
+
+fn keys(profiles: &[Profile]) -> BTreeSet<String> {
+    profiles.iter().map(|p| format!("{}/{}", p.id, p.username)).collect()
+}
+
+fn no_repeats(profiles: &[Profile]) -> bool {
+    keys(profiles).len() == profiles.len()
+}
+
+fn snapshot(followers: Vec<Profile>, following: Vec<Profile>) -> Snapshot {
+    Snapshot {
+        user_id: "42".to_string(),
+        taken_at: 0,
+        source: SnapshotSource::Scan,
+        followers,
+        following,
+    }
+}
+
+proptest! {
+    #[test]
+    fn non_mutuals_and_mutuals_partition_following((followers, following) in lists()) {
+        let mutual = keys(&mutuals(&followers, &following));
+        let non_mutual = keys(&non_mutuals(&followers, &following));
+
+        prop_assert!(mutual.is_disjoint(&non_mutual));
+        prop_assert_eq!(mutual.union(&non_mutual).cloned().collect::<BTreeSet<_>>(), keys(&following));
+    }
+
+    #[test]
+    fn mutuals_and_fans_partition_followers((followers, following) in lists()) {
+        let mutual = keys(&mutuals(&followers, &following));
+        let fan = keys(&fans(&followers, &following));
+
+        prop_assert!(mutual.is_disjoint(&fan));
+        prop_assert_eq!(mutual.union(&fan).cloned().collect::<BTreeSet<_>>(), keys(&followers));
+    }
+
+    #[test]
+    fn categories_list_each_account_once((followers, following) in lists()) {
+        prop_assert!(no_repeats(&mutuals(&followers, &following)));
+        prop_assert!(no_repeats(&non_mutuals(&followers, &following)));
+        prop_assert!(no_repeats(&fans(&followers, &following)));
+    }
+
+    #[test]
+    fn repeats_and_order_dont_change_the_result(
+        ((followers, repeated_followers), (following, repeated_following)) in
+            any::<bool>().prop_flat_map(|with_id| (with_reshuffled(with_id), with_reshuffled(with_id))),
+    ) {
+        prop_assert_eq!(
+            keys(&non_mutuals(&followers, &following)),
+            keys(&non_mutuals(&repeated_followers, &repeated_following))
+        );
+        prop_assert_eq!(
+            keys(&mutuals(&followers, &following)),
+            keys(&mutuals(&repeated_followers, &repeated_following))
+        );
+        prop_assert_eq!(
+            keys(&fans(&followers, &following)),
+            keys(&fans(&repeated_followers, &repeated_following))
+        );
+
+        let counts = relationships(&snapshot(followers, following), None).counts;
+        let repeated = relationships(&snapshot(repeated_followers, repeated_following), None).counts;
+        prop_assert_eq!(
+            (counts.followers, counts.following, counts.mutuals, counts.not_following_back, counts.fans),
+            (repeated.followers, repeated.following, repeated.mutuals, repeated.not_following_back, repeated.fans)
+        );
+    }
+
+    #[test]
+    fn counts_add_up((followers, following) in lists()) {
+        let counts = relationships(&snapshot(followers.clone(), following.clone()), None).counts;
+
+        prop_assert_eq!(counts.followers, keys(&followers).len());
+        prop_assert_eq!(counts.following, keys(&following).len());
+        prop_assert_eq!(counts.mutuals + counts.not_following_back, counts.following);
+        prop_assert_eq!(counts.mutuals + counts.fans, counts.followers);
+    }
+
+    #[test]
+    fn growth_stats_count_like_relationships(
+        (old_followers, old_following, new_followers, new_following) in
+            any::<bool>().prop_flat_map(|with_id| (list(with_id), list(with_id), list(with_id), list(with_id))),
+    ) {
+        let old = snapshot(old_followers, old_following);
+        let new = Snapshot { taken_at: 86_400, ..snapshot(new_followers, new_following) };
+        let stats = growth_stats(&[old.clone(), new.clone()]);
+
+        for (point, current) in stats.points.iter().zip([&old, &new]) {
+            let counts = relationships(current, None).counts;
+            prop_assert_eq!(
+                (point.followers, point.following, point.mutuals),
+                (counts.followers, counts.following, counts.mutuals)
+            );
+        }
+        let lost = diff(&old, &new).lost_followers.len();
+        let old_followers = relationships(&old, None).counts.followers;
+        let expected = if old_followers == 0 { 0.0 } else { lost as f64 / old_followers as f64 };
+        prop_assert!(stats.points[1].churn_rate.is_some_and(|rate| (rate - expected).abs() < 1e-9));
+        prop_assert!(stats.points[1].churn_rate.is_some_and(|rate| rate <= 1.0));
+    }
+
+    #[test]
+    fn traitors_are_the_non_mutuals((followers, following) in lists()) {
+        let expected = non_mutuals(&followers, &following);
+        prop_assert_eq!(keys(&snapshot(followers, following).traitors()), keys(&expected));
+    }
+
+    #[test]
+    fn diff_is_set_difference(
+        (old_followers, old_following, new_followers, new_following) in
+            any::<bool>().prop_flat_map(|with_id| (list(with_id), list(with_id), list(with_id), list(with_id))),
+    ) {
+        let old = snapshot(old_followers, old_following);
+        let new = snapshot(new_followers, new_following);
+        let changes = diff(&old, &new);
+
+        let gained: BTreeSet<_> = keys(&new.followers).difference(&keys(&old.followers)).cloned().collect();
+        prop_assert_eq!(keys(&changes.new_followers), gained);
+        let dropped: BTreeSet<_> = keys(&old.following).difference(&keys(&new.following)).cloned().collect();
+        prop_assert_eq!(keys(&changes.dropped_following), dropped);
+        prop_assert!(no_repeats(&changes.new_followers) && no_repeats(&changes.lost_followers));
+
+        // Swapping the snapshots swaps gains and losses
+        let back = diff(&new, &old);
+        prop_assert_eq!(keys(&back.lost_followers), keys(&changes.new_followers));
+        prop_assert_eq!(keys(&back.new_following), keys(&changes.dropped_following));
+    }
+
+    #[test]
+    fn diff_against_itself_is_empty((followers, following) in lists()) {
+        let current = snapshot(followers, following);
+        let changes = diff(&current, &current);
+
+        prop_assert!(changes.new_followers.is_empty() && changes.lost_followers.is_empty());
+        prop_assert!(changes.new_following.is_empty() && changes.dropped_following.is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+//! Scan pre-flight against the read budget, on a ledger filled at known times
+
+use instafollows_core::budget::{RequestKind, RequestLedger, ScanEstimate};
+use instafollows_core::models::unix_now;
+use instafollows_core::pacing::{Pace, PacingProfile};
+use instafollows_core::settings::Settings;
+use instafollows_core::store::Store;
+
+const ACCOUNT: &str = "42";
+const HOUR_SECS: u64 = 3600;
+
+/// A ledger holding one read at each of `times`
+fn ledger(times: impl IntoIterator<Item = u64>) -> RequestLedger {
+    let store = Store::open_in_memory().unwrap();
+    for at in times {
+        store.record_request(ACCOUNT, RequestKind::Read, at).unwrap();
+    }
+    RequestLedger::with_store(store).unwrap()
+}
+
+/// A request every `delay_ms`, no breaks
+fn steady(delay_ms: u64) -> Pace {
+    Pace {
+        delay_min_ms: delay_ms,
+        delay_max_ms: delay_ms,
+        break_every: 0,
+        break_ms: 0,
+        session_cap: 2000,
+    }
+}
+
+fn estimate(requests: u32) -> ScanEstimate {
+    ScanEstimate {
+        followers: 0,
+        following: 0,
+        requests,
+    }
+}
+
+#[test]
+fn requests_in_a_window_follow_delays_and_breaks() {
+    assert_eq!(steady(36_000).max_requests_in(HOUR_SECS), 101);
+    // 10 cycles of 25 requests and a 3 minute break, then 25 more
+    assert_eq!(PacingProfile::overnight().reads.max_requests_in(HOUR_SECS), 275);
+}
+
+#[test]
+fn scan_under_budget_fits_now() {
+    let now = unix_now();
+    let check = ledger((0..100).map(|i| now - 1000 + i))
+        .preflight(ACCOUNT, estimate(500), &steady(36_000))
+        .unwrap();
+
+    assert_eq!(check.in_window, 101);
+    assert_eq!(check.remaining, 200);
+    assert!(check.fits);
+    assert_eq!(check.next_safe_at, None);
+}
+
+#[test]
+fn next_safe_at_waits_for_enough_requests_to_expire() {
+    let now = unix_now();
+    let times: Vec<u64> = (0..250).map(|i| now - 3000 + i).collect();
+    let check = ledger(times.clone()).preflight(ACCOUNT, estimate(500), &steady(36_000)).unwrap();
+
+    assert_eq!(check.remaining, 50);
+    assert!(!check.fits);
+    // 250 used + 101 needed - 300 allowed: the 51st oldest must expire
+    assert_eq!(check.next_safe_at, Some(times[50] + HOUR_SECS));
+}
+
+#[test]
+fn requests_outside_the_window_are_not_counted() {
+    let now = unix_now();
+    let check = ledger((0..250).map(|i| now - 2 * HOUR_SECS + i))
+        .preflight(ACCOUNT, estimate(500), &steady(36_000))
+        .unwrap();
+
+    assert_eq!(check.remaining, 300);
+    assert!(check.fits);
+}
+
+#[test]
+fn scan_faster_than_the_budget_never_fits() {
+    let check = ledger([]).preflight(ACCOUNT, estimate(1000), &steady(1000)).unwrap();
+
+    assert_eq!(check.in_window, 1000);
+    assert!(!check.fits);
+    assert_eq!(check.next_safe_at, None);
+}
+
+#[test]
+fn large_scan_fits_overnight_but_not_at_standard_pace() {
+    let large = ScanEstimate::new(20_000, 20_000, 50);
+    let ledger = ledger([]);
+
+    let overnight = ledger.preflight(ACCOUNT, large, &PacingProfile::overnight().reads).unwrap();
+    assert_eq!(overnight.in_window, 275);
+    assert!(overnight.fits);
+
+    let standard = ledger
+        .preflight(ACCOUNT, large, &PacingProfile::standard(&Settings::default()).reads)
+        .unwrap();
+    assert_eq!(standard.in_window, large.requests);
+    assert!(!standard.fits);
+    assert_eq!(standard.next_safe_at, None);
+}
@@ -0,0 +1,32 @@
+//! Exported logs are scrubbed again, whatever build wrote them
+
+use instafollows_core::logging::{export_logs, LOG_FILE};
+use serde_json::{json, Value};
+use std::fs;
+
+#[test]
+fn export_scrubs_secrets_in_escaped_json_messages() {
+    let dir = std::env::temp_dir().join(format!("instafollows-logging-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    // As an older build would have logged a response body, unscrubbed
+    let lines = [
+        json!({ "level": "DEBUG", "msg": r#"Body: {"sessionid":"s3cr3t","csrf_token": "s3cr3t"}"# }).to_string(),
+        json!({ "level": "INFO", "msg": "Cookie: sessionid=s3cr3t" }).to_string(),
+        "not json: csrftoken=s3cr3t".to_string(),
+    ];
+    assert!(lines[0].contains(r#"\"sessionid\":\"s3cr3t\""#));
+    fs::write(dir.join(LOG_FILE), lines.join("\n")).unwrap();
+
+    let out = dir.join("export.log");
+    assert_eq!(export_logs(&dir, &out).unwrap(), 3);
+
+    let exported = fs::read_to_string(&out).unwrap();
+    assert!(!exported.contains("s3cr3t"), "{}", exported);
+    let first: Value = serde_json::from_str(exported.lines().next().unwrap()).unwrap();
+    assert_eq!(first["msg"], r#"Body: {"sessionid":"[redacted]","csrf_token": "[redacted]"}"#);
+    assert_eq!(first["level"], "DEBUG");
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,78 @@
+//! Recordings are appended a line at a time and read back for replay
+
+use anyhow::anyhow;
+use instafollows_core::recording::{HttpReply, Recorder, Recording, RECORDING_VERSION};
+use instafollows_core::replay::Replayer;
+use rquest::header::{HeaderMap, HeaderValue};
+use rquest::{Method, StatusCode};
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("instafollows-{}-{}.jsonl", name, std::process::id()))
+}
+
+fn reply(body: &str) -> HttpReply {
+    HttpReply {
+        status: StatusCode::OK,
+        headers: HeaderMap::new(),
+        body: body.as_bytes().to_vec(),
+    }
+}
+
+#[test]
+fn each_exchange_is_appended_as_a_line() {
+    let path = temp_file("append");
+    let recorder = Recorder::create(&path).unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert("x-csrftoken", HeaderValue::from_static("s3cr3t"));
+
+    let url = "https://www.instagram.com/api/v1/friendships/42/followers/?count=50";
+    recorder.record(&Method::GET, url, &headers, &[], &Ok(reply(r#"{"users": []}"#)), Duration::from_millis(120));
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+    recorder.record(&Method::GET, url, &headers, &[], &Err(anyhow!("connection reset")), Duration::from_millis(40));
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(text.lines().count(), 3);
+    assert!(!text.contains("s3cr3t"));
+
+    let recording = Recording::load(&path).unwrap();
+    assert_eq!(recording.version, RECORDING_VERSION);
+    assert_eq!(recording.entries.len(), 2);
+    assert_eq!(recording.entries[0].request.query[0].value, "50");
+    assert_eq!(recording.entries[0].response.as_ref().unwrap().body, r#"{"users": []}"#);
+    assert_eq!(recording.entries[1].error.as_deref(), Some("connection reset"));
+
+    // And replays in the order it was recorded
+    let replayer = Replayer::new(recording);
+    assert!(replayer.reply(&Method::GET, &Url::parse(url).unwrap()).is_ok());
+    assert!(replayer.reply(&Method::GET, &Url::parse(url).unwrap()).is_err());
+    assert_eq!(replayer.remaining(), 0);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn saved_document_still_loads() {
+    let path = temp_file("document");
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/graphql_followers_paged.json");
+    let recording = Recording::load(fixture).unwrap();
+    recording.save(&path).unwrap();
+
+    assert_eq!(Recording::load(&path).unwrap().entries.len(), recording.entries.len());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn broken_line_is_reported() {
+    let path = temp_file("broken");
+    Recorder::create(&path).unwrap();
+    std::fs::write(&path, std::fs::read_to_string(&path).unwrap() + "{\"started\":\n").unwrap();
+
+    let err = Recording::load(&path).unwrap_err();
+    assert!(err.to_string().contains("line 2"), "{}", err);
+
+    let _ = std::fs::remove_file(&path);
+}
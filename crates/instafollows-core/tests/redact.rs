@@ -0,0 +1,83 @@
+//! Secrets stay out of logs, whichever shape they come in
+
+use instafollows_core::redact::{redact, redact_header, redact_headers, CsrfToken, SessionId, REDACTED};
+use rquest::header::{HeaderMap, HeaderValue};
+
+const SECRET: &str = "s3cr3t";
+
+#[test]
+fn masks_cookie_pairs() {
+    let redacted = redact("sessionid=s3cr3t; csrftoken=s3cr3t; ds_user_id=42");
+    assert_eq!(redacted, "sessionid=[redacted]; csrftoken=[redacted]; ds_user_id=42");
+}
+
+#[test]
+fn masks_every_spelling_of_the_csrf_token() {
+    for text in [
+        "csrftoken=s3cr3t",
+        "csrf_token=s3cr3t",
+        "csrf-token: s3cr3t",
+        "x-csrftoken: s3cr3t",
+        r#"{"csrf_token":"s3cr3t"}"#,
+        r#"{"config":{"CSRF_TOKEN": "s3cr3t"}}"#,
+    ] {
+        let redacted = redact(text);
+        assert!(!redacted.contains(SECRET) && redacted.contains(REDACTED), "{}", redacted);
+    }
+}
+
+#[test]
+fn masks_json_fields_and_query_values() {
+    assert_eq!(
+        redact(r#"{"sessionid": "s3cr3t", "username": "someone"}"#),
+        r#"{"sessionid": "[redacted]", "username": "someone"}"#
+    );
+    assert_eq!(redact("variables=x&mid=s3cr3t&first=50"), "variables=x&mid=[redacted]&first=50");
+}
+
+#[test]
+fn masks_whole_cookie_and_auth_header_lines() {
+    let redacted = redact("GET /\nCookie: a=1; b=s3cr3t\nAuthorization: Bearer s3cr3t\nAccept: */*");
+    assert_eq!(redacted, "GET /\nCookie: [redacted]\nAuthorization: [redacted]\nAccept: */*");
+}
+
+#[test]
+fn leaves_other_text_alone() {
+    let text = "Fetched 50 followers of 42 (page 2)";
+    assert_eq!(redact(text), text);
+}
+
+#[test]
+fn secret_headers_are_masked_whole() {
+    let value = HeaderValue::from_static("s3cr3t");
+    for name in ["cookie", "set-cookie", "x-csrftoken", "authorization"] {
+        assert_eq!(redact_header(name, &value), REDACTED);
+    }
+    assert_eq!(redact_header("accept", &HeaderValue::from_static("*/*")), "*/*");
+    assert_eq!(
+        redact_header("referer", &HeaderValue::from_static("https://x/?sessionid=s3cr3t")),
+        "https://x/?sessionid=[redacted]"
+    );
+}
+
+#[test]
+fn header_map_is_one_masked_line() {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-csrftoken", HeaderValue::from_static("s3cr3t"));
+    headers.insert("x-ig-app-id", HeaderValue::from_static("936619743392459"));
+
+    assert_eq!(redact_headers(&headers), "x-csrftoken: [redacted], x-ig-app-id: 936619743392459");
+}
+
+#[test]
+fn secret_types_never_print_their_value() {
+    let session = SessionId::new(SECRET);
+    let csrf = CsrfToken::new(SECRET);
+
+    assert_eq!(format!("{:?}", session), "SessionId([redacted])");
+    assert_eq!(format!("{:?}", csrf), "CsrfToken([redacted])");
+    assert_eq!(session.to_string(), REDACTED);
+    assert_eq!(csrf.to_string(), REDACTED);
+    assert_eq!(format!("{:?}", Some(&session)), "Some(SessionId([redacted]))");
+    assert_eq!(session.expose(), SECRET);
+}
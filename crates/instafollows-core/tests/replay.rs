@@ -0,0 +1,118 @@
+//! Pagination regressions replayed from recorded sessions in `fixtures/`
+
+use instafollows_core::progress::{ProgressEvent, ProgressSink};
+use instafollows_core::endpoints::EndpointConfig;
+use instafollows_core::replay::Replayer;
+use instafollows_core::settings::{Settings, SharedSettings};
+use instafollows_core::GhostClient;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct Events(Mutex<Vec<ProgressEvent>>);
+
+impl ProgressSink for Events {
+    fn report(&self, event: ProgressEvent) {
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+impl Events {
+    fn retries(&self) -> usize {
+        self.0.lock().unwrap().iter().filter(|e| matches!(e, ProgressEvent::Retry { .. })).count()
+    }
+}
+
+fn replaying(fixture: &str) -> (GhostClient, Arc<Replayer>) {
+    replaying_with(fixture, Settings::default())
+}
+
+fn replaying_with(fixture: &str, settings: Settings) -> (GhostClient, Arc<Replayer>) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(fixture);
+    let replayer = Arc::new(Replayer::load(path).unwrap());
+    let mut client = GhostClient::with_settings(EndpointConfig::default(), SharedSettings::new(settings)).unwrap();
+    client.set_replay(Some(replayer.clone()));
+    (client, replayer)
+}
+
+fn ids(profiles: &[instafollows_core::Profile]) -> Vec<&str> {
+    profiles.iter().map(|p| p.id.as_str()).collect()
+}
+
+#[tokio::test]
+async fn follows_the_after_cursor_across_pages() {
+    let (client, replayer) = replaying("graphql_followers_paged.json");
+    let events = Events::default();
+
+    let followers = client.fetch_followers("42", &events).await.unwrap();
+
+    assert_eq!(ids(&followers), ["1", "2", "3", "4", "5"]);
+    assert_eq!(replayer.remaining(), 0);
+}
+
+#[tokio::test]
+async fn retries_a_failed_page_with_the_same_cursor() {
+    let (client, _replayer) = replaying("graphql_followers_paged.json");
+    let events = Events::default();
+
+    client.fetch_followers("42", &events).await.unwrap();
+
+    // A dropped connection, then a 429, both on page 2
+    assert_eq!(events.retries(), 2);
+}
+
+#[tokio::test]
+async fn repeated_cursor_falls_back_to_friendships() {
+    let (client, replayer) = replaying("graphql_stuck_cursor.json");
+    let events = Events::default();
+
+    let followers = client.fetch_followers("42", &events).await.unwrap();
+
+    assert_eq!(ids(&followers), ["1", "2", "3", "4"]);
+    assert_eq!(replayer.remaining(), 0);
+}
+
+#[tokio::test]
+async fn missing_end_cursor_falls_back_to_friendships() {
+    let (client, replayer) = replaying("graphql_missing_cursor.json");
+    let events = Events::default();
+
+    let followers = client.fetch_followers("42", &events).await.unwrap();
+
+    assert_eq!(ids(&followers), ["1", "2", "3", "4"]);
+    assert_eq!(replayer.remaining(), 0);
+}
+
+#[tokio::test]
+async fn account_repeated_on_the_next_page_is_listed_once() {
+    let (client, replayer) = replaying("graphql_followers_repeated.json");
+    let events = Events::default();
+
+    let followers = client.fetch_followers("42", &events).await.unwrap();
+
+    assert_eq!(ids(&followers), ["1", "2", "3", "4"]);
+    assert_eq!(replayer.remaining(), 0);
+}
+
+#[tokio::test]
+async fn replays_under_a_different_page_size() {
+    // Recorded with pages of 50, on both GraphQL and friendships
+    for fixture in ["graphql_followers_paged.json", "graphql_stuck_cursor.json"] {
+        let (client, replayer) = replaying_with(fixture, Settings { page_size: 10, ..Settings::default() });
+        let events = Events::default();
+
+        client.fetch_followers("42", &events).await.unwrap();
+
+        assert_eq!(replayer.remaining(), 0, "{}", fixture);
+    }
+}
+
+#[tokio::test]
+async fn unrecorded_request_is_an_error() {
+    let (client, _replayer) = replaying("graphql_followers_paged.json");
+    let events = Events::default();
+
+    let err = client.fetch_following("42", &events).await.unwrap_err();
+
+    assert!(err.to_string().contains("No recorded response left for GET /graphql/query"), "{}", err);
+}
@@ -0,0 +1,71 @@
+//! When the next scheduled scan is due, in local time
+
+use chrono::{Local, NaiveDate, TimeZone};
+use instafollows_core::schedule::ScheduleConfig;
+
+/// Unix time of a local time in June, clear of any DST change
+fn at(day: u32, hour: u32, minute: u32) -> u64 {
+    let local = NaiveDate::from_ymd_opt(2026, 6, day).unwrap().and_hms_opt(hour, minute, 0).unwrap();
+    Local.from_local_datetime(&local).single().unwrap().timestamp() as u64
+}
+
+fn daily(window_start_hour: u32, window_end_hour: u32) -> ScheduleConfig {
+    ScheduleConfig {
+        enabled: true,
+        accounts: vec!["42".to_string()],
+        window_start_hour,
+        window_end_hour,
+        ..ScheduleConfig::default()
+    }
+}
+
+#[test]
+fn first_run_waits_for_the_window() {
+    let schedule = daily(3, 6);
+
+    assert_eq!(schedule.next_run(None, at(10, 14, 0)), at(11, 3, 0));
+    assert_eq!(schedule.next_run(None, at(10, 1, 0)), at(10, 3, 0));
+    assert_eq!(schedule.next_run(None, at(10, 4, 30)), at(10, 4, 30));
+}
+
+#[test]
+fn daily_run_may_come_a_little_early() {
+    let schedule = daily(3, 6);
+
+    // Due at 00:46 with the 10% slack, so the window start
+    assert_eq!(schedule.next_run(Some(at(9, 3, 10)), at(9, 10, 0)), at(10, 3, 0));
+    // Still inside the window
+    assert_eq!(schedule.next_run(Some(at(9, 5, 30)), at(9, 10, 0)), at(10, 3, 6));
+}
+
+#[test]
+fn overdue_run_outside_the_window_waits_for_the_next_one() {
+    let schedule = daily(3, 6);
+
+    // Last run yesterday, app reopened this afternoon
+    assert_eq!(schedule.next_run(Some(at(9, 3, 0)), at(10, 14, 0)), at(11, 3, 0));
+    // Reopened inside the window: run now
+    assert_eq!(schedule.next_run(Some(at(9, 3, 0)), at(10, 4, 30)), at(10, 4, 30));
+    // Days overdue
+    assert_eq!(schedule.next_run(Some(at(1, 3, 0)), at(10, 23, 0)), at(11, 3, 0));
+}
+
+#[test]
+fn window_wraps_past_midnight() {
+    let schedule = daily(22, 2);
+
+    assert_eq!(schedule.next_run(None, at(10, 14, 0)), at(10, 22, 0));
+    assert_eq!(schedule.next_run(None, at(10, 23, 30)), at(10, 23, 30));
+    assert_eq!(schedule.next_run(None, at(11, 1, 0)), at(11, 1, 0));
+    assert_eq!(schedule.next_run(None, at(11, 2, 30)), at(11, 22, 0));
+    // Last run just before midnight, due early the night after
+    assert_eq!(schedule.next_run(Some(at(10, 23, 0)), at(11, 9, 0)), at(11, 22, 0));
+}
+
+#[test]
+fn window_of_equal_hours_is_any_time() {
+    let schedule = daily(0, 0);
+
+    assert_eq!(schedule.next_run(None, at(10, 14, 0)), at(10, 14, 0));
+    assert_eq!(schedule.next_run(Some(at(10, 14, 0)), at(10, 15, 0)), at(11, 11, 36));
+}
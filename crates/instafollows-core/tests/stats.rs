@@ -0,0 +1,174 @@
+//! Follow spans, tenure, departures and quick unfollowers over a series of
+//! snapshots
+
+use instafollows_core::models::SnapshotSource;
+use instafollows_core::stats::{
+    follow_durations, follower_spans, quick_unfollowers, FollowCycler, QuickUnfollowOptions,
+};
+use instafollows_core::{Profile, Snapshot};
+use serde_json::json;
+
+const DAY: u64 = 86_400;
+
+fn profile(id: &str) -> Profile {
+    Profile::from_json(&json!({ "id": id, "username": format!("user{}", id) }))
+}
+
+/// Followers on day `day`
+fn on_day(day: u64, followers: &[&str]) -> Snapshot {
+    Snapshot {
+        user_id: "42".to_string(),
+        taken_at: day * DAY,
+        source: SnapshotSource::Scan,
+        followers: followers.iter().map(|id| profile(id)).collect(),
+        following: Vec::new(),
+    }
+}
+
+fn cyclers(snapshots: &[Snapshot], window_days: f64, min_cycles: usize) -> Vec<FollowCycler> {
+    quick_unfollowers(snapshots, &QuickUnfollowOptions { window_days, min_cycles })
+}
+
+fn ids<'a>(profiles: impl IntoIterator<Item = &'a Profile>) -> Vec<&'a str> {
+    profiles.into_iter().map(|p| p.id.as_str()).collect()
+}
+
+#[test]
+fn durations_split_current_and_departed_followers() {
+    let snapshots = [on_day(0, &["1", "2"]), on_day(3, &["1", "2", "3"]), on_day(10, &["1", "3"])];
+
+    let durations = follow_durations(&snapshots);
+
+    assert_eq!(ids(durations.current.iter().map(|t| &t.profile)), ["1", "3"]);
+    let (old, new) = (&durations.current[0], &durations.current[1]);
+    assert_eq!((old.following_since, old.days, old.predates_history), (0, 10.0, true));
+    assert_eq!((new.following_since, new.days, new.predates_history), (3 * DAY, 7.0, false));
+
+    assert_eq!(ids(durations.departed.iter().map(|d| &d.profile)), ["2"]);
+    let left = &durations.departed[0];
+    assert_eq!((left.first_seen, left.last_seen, left.days, left.predates_history), (0, 3 * DAY, 3.0, true));
+}
+
+#[test]
+fn departures_are_newest_first_and_returns_are_new_stays() {
+    let snapshots = [on_day(0, &["1", "2"]), on_day(1, &["2"]), on_day(2, &["1"]), on_day(4, &[])];
+
+    let durations = follow_durations(&snapshots);
+
+    assert!(durations.current.is_empty());
+    let departed: Vec<(&str, u64)> = durations.departed.iter().map(|d| (d.profile.id.as_str(), d.last_seen)).collect();
+    assert_eq!(departed, [("1", 2 * DAY), ("2", DAY), ("1", 0)]);
+    assert!(!durations.departed[0].predates_history);
+}
+
+#[test]
+fn follower_listed_twice_keeps_their_tenure() {
+    // A page shifted mid-scan and repeated follower 1 on day 5
+    let snapshots = [on_day(0, &["1"]), on_day(5, &["1", "2", "1"]), on_day(9, &["1", "2"])];
+
+    let spans = follower_spans(&snapshots);
+
+    assert_eq!(spans.len(), 2);
+    assert_eq!((spans[0].profile.id.as_str(), spans[0].first_seen, spans[0].days()), ("1", 0, 9.0));
+    assert!(spans.iter().all(|s| s.ongoing));
+}
+
+#[test]
+fn snapshots_in_any_order_give_the_same_spans() {
+    let oldest_first = [on_day(0, &["1"]), on_day(2, &["1", "2"]), on_day(6, &["2"])];
+    let shuffled = [on_day(6, &["2"]), on_day(0, &["1"]), on_day(2, &["1", "2"])];
+
+    let summary = |snapshots: &[Snapshot]| -> Vec<(String, u64, u64, bool)> {
+        follower_spans(snapshots)
+            .into_iter()
+            .map(|s| (s.profile.id, s.first_seen, s.last_seen, s.ongoing))
+            .collect()
+    };
+    assert_eq!(summary(&shuffled), summary(&oldest_first));
+
+    let durations = follow_durations(&shuffled);
+    assert_eq!((durations.departed[0].days, durations.departed[0].predates_history), (2.0, true));
+    assert_eq!((durations.current[0].days, durations.current[0].predates_history), (4.0, false));
+}
+
+#[test]
+fn unfollow_inside_the_window_is_quick() {
+    // 1 stays 3 days, 2 exactly the 7 day window
+    let snapshots = [
+        on_day(0, &[]),
+        on_day(1, &["1", "2"]),
+        on_day(4, &["1", "2"]),
+        on_day(8, &["2"]),
+        on_day(9, &[]),
+    ];
+
+    let found = cyclers(&snapshots, 7.0, 2);
+
+    assert_eq!(ids(found.iter().map(|c| &c.profile)), ["2", "1"]);
+    assert!(found.iter().all(|c| c.quick && !c.repeated && !c.following_now));
+    assert_eq!(found[1].stays[0].days, 3.0);
+}
+
+#[test]
+fn unfollow_just_outside_the_window_is_not_quick() {
+    let snapshots = [on_day(0, &[]), on_day(1, &["1"]), on_day(9, &["1"]), on_day(10, &[])];
+
+    assert!(cyclers(&snapshots, 7.0, 2).is_empty());
+    assert_eq!(ids(cyclers(&snapshots, 8.0, 2).iter().map(|c| &c.profile)), ["1"]);
+}
+
+#[test]
+fn follower_predating_the_history_is_not_quick() {
+    // Already following in the oldest snapshot: the stay may be much longer
+    let snapshots = [on_day(0, &["1"]), on_day(1, &[])];
+
+    assert!(cyclers(&snapshots, 7.0, 2).is_empty());
+}
+
+#[test]
+fn repeated_cycles_count_from_min_cycles() {
+    // Two month-long stays, both ended, then back again
+    let snapshots = [
+        on_day(0, &[]),
+        on_day(1, &["1"]),
+        on_day(31, &["1"]),
+        on_day(32, &[]),
+        on_day(40, &["1"]),
+        on_day(70, &["1"]),
+        on_day(71, &[]),
+        on_day(80, &["1"]),
+    ];
+
+    let found = cyclers(&snapshots, 7.0, 2);
+    assert_eq!(found.len(), 1);
+    let cycler = &found[0];
+    assert!(cycler.repeated && !cycler.quick && cycler.following_now);
+    let stays: Vec<(u64, bool)> = cycler.stays.iter().map(|s| (s.first_seen / DAY, s.ended)).collect();
+    assert_eq!(stays, [(1, true), (40, true), (80, false)]);
+
+    assert!(cyclers(&snapshots, 7.0, 3).is_empty());
+    // A minimum of 0 still needs one ended stay
+    assert_eq!(cyclers(&snapshots, 7.0, 0).len(), 1);
+    assert!(cyclers(&[on_day(0, &[]), on_day(40, &["1"])], 7.0, 0).is_empty());
+}
+
+#[test]
+fn newest_departure_comes_first() {
+    let snapshots = [
+        on_day(0, &[]),
+        on_day(1, &["1", "2", "3"]),
+        on_day(2, &["2", "3"]),
+        on_day(3, &["2"]),
+        on_day(4, &["1"]),
+        on_day(5, &[]),
+    ];
+
+    let found = cyclers(&snapshots, 7.0, 2);
+
+    // Last seen before leaving: 1 on day 4 (the second time), 2 on day 3, 3 on day 2
+    let order: Vec<(&str, Option<u64>)> = found
+        .iter()
+        .map(|c| (c.profile.id.as_str(), c.stays.iter().filter(|s| s.ended).map(|s| s.last_seen / DAY).max()))
+        .collect();
+    assert_eq!(order, [("1", Some(4)), ("2", Some(3)), ("3", Some(2))]);
+}
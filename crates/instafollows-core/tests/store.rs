@@ -0,0 +1,60 @@
+//! Snapshots saved to and read back from the local store
+
+use instafollows_core::analysis::diff;
+use instafollows_core::models::SnapshotSource;
+use instafollows_core::store::{ProfileField, Store};
+use instafollows_core::{Profile, Snapshot};
+use serde_json::json;
+
+fn profile(id: &str, username: &str) -> Profile {
+    Profile::from_json(&json!({ "id": id, "username": username, "full_name": "Someone" }))
+}
+
+fn snapshot(taken_at: u64, followers: Vec<Profile>) -> Snapshot {
+    Snapshot {
+        user_id: "42".to_string(),
+        taken_at,
+        source: SnapshotSource::Scan,
+        following: followers.clone(),
+        followers,
+    }
+}
+
+#[test]
+fn rename_is_a_profile_change_not_a_new_follower() {
+    let mut store = Store::open_in_memory().unwrap();
+    store.save_snapshot(&snapshot(1000, vec![profile("1", "before"), profile("2", "steady")])).unwrap();
+    let latest_id = store.save_snapshot(&snapshot(2000, vec![profile("1", "after"), profile("2", "steady")])).unwrap();
+
+    let history = store.profile_history("1").unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].field, ProfileField::Username);
+    assert_eq!((history[0].old_value.as_str(), history[0].new_value.as_str()), ("before", "after"));
+    assert_eq!(history[0].detected_at, 2000);
+    assert_eq!(history[0].snapshot_id, Some(latest_id));
+    assert!(store.profile_history("2").unwrap().is_empty());
+    assert_eq!(store.profile("1").unwrap().unwrap().username, "after");
+
+    let snapshots = store.snapshots("42", None).unwrap();
+    let (previous, latest) = (&snapshots[0], &snapshots[1]);
+    assert_eq!((previous.taken_at, latest.taken_at), (1000, 2000));
+    let changes = diff(previous, latest);
+    assert!(changes.new_followers.is_empty() && changes.lost_followers.is_empty());
+    assert!(changes.new_following.is_empty() && changes.dropped_following.is_empty());
+}
+
+#[test]
+fn older_snapshot_saved_later_keeps_the_current_name() {
+    let mut store = Store::open_in_memory().unwrap();
+    store.save_snapshot(&snapshot(2000, vec![profile("1", "after")])).unwrap();
+    store.save_snapshot(&snapshot(1000, vec![profile("1", "before")])).unwrap();
+
+    assert_eq!(store.profile("1").unwrap().unwrap().username, "after");
+    assert!(store.profile_history("1").unwrap().is_empty());
+
+    // A newer scan still renames from the current name
+    store.save_snapshot(&snapshot(3000, vec![profile("1", "latest")])).unwrap();
+    let history = store.profile_history("1").unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!((history[0].old_value.as_str(), history[0].new_value.as_str()), ("after", "latest"));
+}
@@ -2,11 +2,38 @@
 //! 
 //! Commands for the frontend to interact with the Ghost Client
 
-mod network;
+use instafollows_core::analysis::{relationships, Category, RelationshipCounts, Relationships};
+use instafollows_core::budget::{BudgetConfig, BudgetExceeded, BudgetReport, RequestLedger, ScanPreflight, ANONYMOUS_ACCOUNT};
+use instafollows_core::endpoints::{EndpointConfig, ENDPOINTS_FILE};
+use instafollows_core::filters::{Filter, RuleSet};
+use instafollows_core::logging::{self, init_logging, set_level};
+use instafollows_core::models::unix_now;
+use instafollows_core::notify::{ChangeCounts, NotificationConfig, NOTIFICATIONS_SETTING, NOTIFICATION_TITLE};
+use instafollows_core::pacing::{pacing_profiles, PacingProfile};
+use instafollows_core::progress::{LogSink, ProgressEvent, ProgressSink};
+use instafollows_core::recording::RECORDING_FILE;
+use instafollows_core::replay::Replayer;
+use instafollows_core::schedule::{ScanSummary, ScheduleConfig, LAST_RUNS_SETTING, SCHEDULE_SETTING};
+use instafollows_core::search::{ProfileList, ProfilePage, ProfileQuery, ScanView};
+use instafollows_core::settings::{Settings, SharedSettings, SETTINGS_FILE};
+use instafollows_core::stats::{
+    follow_durations, growth_stats, quick_unfollowers, FollowCycler, FollowDurations, GrowthStats, QuickUnfollowOptions,
+};
+use instafollows_core::storage::{read_rule_sets, write_rule_sets, RULES_FILE, SESSION_FILE};
+use instafollows_core::store::{ActionKind, ActionRecord, ProfileChange, SnapshotInfo, Store, DATABASE_FILE};
+use instafollows_core::{GhostClient, Profile, ScanResult, Snapshot};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::{Mutex, RwLock};
 
-use network::{get_stealth_integrity, GhostClient, Profile, ScanResult};
-use tauri::State;
-use tokio::sync::Mutex;
+/// How often the scheduler checks for due scans
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
 
 // ============================================
 // APPLICATION STATE
@@ -14,6 +41,132 @@ use tokio::sync::Mutex;
 
 struct AppState {
     client: Mutex<GhostClient>,
+    /// User ID of the loaded session, mirrored from the client on every
+    /// session load so it can be read while a scan holds the client
+    account: RwLock<Option<String>>,
+    /// Snapshots, profiles and the action log
+    store: Mutex<Store>,
+    /// Indexed lists of the last scan per account, served page by page
+    views: Mutex<HashMap<String, ScanView>>,
+    /// Requests sent per account, shared with the client
+    ledger: Arc<RequestLedger>,
+    /// Network settings, shared with the client
+    settings: SharedSettings,
+    /// `settings.json` in the app config directory
+    settings_path: PathBuf,
+    /// Rotating diagnostics log, in the app log directory
+    log_dir: PathBuf,
+    /// Saved rule sets, in the app data directory
+    rules_path: PathBuf,
+    /// Endpoint overrides, in the app data directory
+    endpoints_path: PathBuf,
+}
+
+impl AppState {
+    /// Account the ledger counts requests against
+    async fn ledger_account(&self) -> String {
+        self.account.read().await.clone().unwrap_or_else(|| ANONYMOUS_ACCOUNT.to_string())
+    }
+
+    /// Persist a fresh scan, index it for querying and notify about changes,
+    /// returning its report against the previous stored one
+    async fn store_scan<R: Runtime>(&self, app: &AppHandle<R>, snapshot: Snapshot) -> Result<Relationships, String> {
+        let previous = {
+            let mut store = self.store.lock().await;
+            let previous = store.latest_snapshot(&snapshot.user_id).map_err(|e| e.to_string())?;
+            store.save_snapshot(&snapshot).map_err(|e| e.to_string())?;
+            previous
+        };
+
+        if let Some(previous) = &previous {
+            self.notify_changes(app, previous, &snapshot).await;
+        }
+
+        let report = relationships(&snapshot, previous.as_ref());
+        self.views.lock().await.insert(snapshot.user_id.clone(), ScanView::new(&snapshot));
+        Ok(report)
+    }
+
+    /// Desktop notification summarising changes since the previous scan
+    async fn notify_changes<R: Runtime>(&self, app: &AppHandle<R>, previous: &Snapshot, current: &Snapshot) {
+        let config: NotificationConfig = match self.store.lock().await.setting(NOTIFICATIONS_SETTING) {
+            Ok(config) => config.unwrap_or_default(),
+            Err(e) => {
+                log::warn!("Notification settings: {}", e);
+                return;
+            }
+        };
+
+        if let Some(body) = config.message(&ChangeCounts::between(previous, current)) {
+            if let Err(e) = app.notification().builder().title(NOTIFICATION_TITLE).body(body).show() {
+                log::warn!("Notification failed: {}", e);
+            }
+        }
+    }
+
+    async fn latest_snapshot(&self, user_id: &str) -> Result<Snapshot, String> {
+        self.store
+            .lock()
+            .await
+            .latest_snapshot(user_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "No scan for this account yet".to_string())
+    }
+}
+
+/// Forwards network progress to the frontend. Every event goes out as
+/// `scan_event`; page updates also keep the `scan_progress` shape the
+/// progress bar listens to.
+struct TauriSink<'a, R: Runtime>(&'a tauri::Window<R>);
+
+impl<R: Runtime> ProgressSink for TauriSink<'_, R> {
+    fn report(&self, event: ProgressEvent) {
+        if let ProgressEvent::Page { stage, page, current, total, eta_ms } = &event {
+            let _ = self.0.emit("scan_progress", serde_json::json!({
+                "stage": stage,
+                "current": current,
+                "total": total,
+                "page": page,
+                "eta_ms": eta_ms
+            }));
+        }
+        let _ = self.0.emit("scan_event", &event);
+    }
+}
+
+/// Error of a scan command. A scan refused by the pre-flight keeps its
+/// details, so the frontend can show when the next safe window opens.
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum ScanError {
+    BudgetExceeded { message: String, preflight: ScanPreflight },
+    Failed { message: String },
+}
+
+impl From<anyhow::Error> for ScanError {
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast::<BudgetExceeded>() {
+            Ok(exceeded) => ScanError::BudgetExceeded {
+                message: exceeded.to_string(),
+                preflight: exceeded.0,
+            },
+            Err(e) => ScanError::Failed { message: e.to_string() },
+        }
+    }
+}
+
+impl From<String> for ScanError {
+    fn from(message: String) -> Self {
+        ScanError::Failed { message }
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::BudgetExceeded { message, .. } | ScanError::Failed { message } => f.write_str(message),
+        }
+    }
 }
 
 // ============================================
@@ -24,7 +177,9 @@ struct AppState {
 #[tauri::command]
 async fn load_session(state: State<'_, AppState>, path: String) -> Result<String, String> {
     let mut client = state.client.lock().await;
-    client.load_session(&path).map_err(|e| e.to_string())
+    let result = client.load_session(&path);
+    *state.account.write().await = client.user_id.clone();
+    result.map_err(|e| e.to_string())
 }
 
 /// Warmup connection (establish Keep-Alive)
@@ -43,43 +198,279 @@ async fn get_user_id(state: State<'_, AppState>, username: String) -> Result<Str
 
 /// Scan for traitors (people you follow who don't follow back)
 #[tauri::command]
-async fn scan_traitors(state: State<'_, AppState>, window: tauri::Window, user_id: String) -> Result<ScanResult, String> {
-    let client = state.client.lock().await;
-    client.find_traitors(&user_id, &window).await.map_err(|e| e.to_string())
+async fn scan_traitors<R: Runtime>(
+    state: State<'_, AppState>,
+    window: tauri::Window<R>,
+    user_id: String,
+    pacing: Option<String>,
+) -> Result<ScanResult, ScanError> {
+    let start = std::time::Instant::now();
+    let snapshot = {
+        let client = state.client.lock().await;
+        client
+            .fetch_snapshot(&user_id, pacing.as_deref(), &TauriSink(&window))
+            .await?
+    };
+
+    let result = ScanResult {
+        traitors: snapshot.traitors(),
+        total_followers: snapshot.followers.len() as u32,
+        total_following: snapshot.following.len() as u32,
+        scan_time_ms: start.elapsed().as_millis() as u64,
+    };
+    state.store_scan(window.app_handle(), snapshot).await?;
+    Ok(result)
+}
+
+/// Scan every relationship category, optionally keeping only some lists
+#[tauri::command]
+async fn scan_relationships<R: Runtime>(
+    state: State<'_, AppState>,
+    window: tauri::Window<R>,
+    user_id: String,
+    categories: Option<Vec<Category>>,
+    pacing: Option<String>,
+) -> Result<Relationships, ScanError> {
+    let snapshot = {
+        let client = state.client.lock().await;
+        client
+            .fetch_snapshot(&user_id, pacing.as_deref(), &TauriSink(&window))
+            .await?
+    };
+
+    let report = state.store_scan(window.app_handle(), snapshot).await?;
+    Ok(match categories {
+        Some(categories) => report.only(&categories),
+        None => report,
+    })
+}
+
+/// Scan an account and keep the lists in the backend, returning only the
+/// counts. Pages of each list are then fetched with `query_profiles`.
+#[tauri::command]
+async fn scan_account<R: Runtime>(
+    state: State<'_, AppState>,
+    window: tauri::Window<R>,
+    user_id: String,
+    pacing: Option<String>,
+) -> Result<RelationshipCounts, ScanError> {
+    let snapshot = {
+        let client = state.client.lock().await;
+        client
+            .fetch_snapshot(&user_id, pacing.as_deref(), &TauriSink(&window))
+            .await?
+    };
+    Ok(state.store_scan(window.app_handle(), snapshot).await?.counts)
+}
+
+/// One sorted, searched, optionally rule-filtered window of a list from the
+/// last scan of an account
+#[tauri::command]
+async fn query_profiles(
+    state: State<'_, AppState>,
+    user_id: String,
+    list: ProfileList,
+    query: ProfileQuery,
+    rule_set: Option<String>,
+) -> Result<ProfilePage, String> {
+    let filter = rule_set.map(|name| load_filter(&state.rules_path, &name)).transpose()?;
+
+    let mut views = state.views.lock().await;
+    if !views.contains_key(&user_id) {
+        // First query since startup: index the stored scan
+        let snapshot = state.latest_snapshot(&user_id).await?;
+        views.insert(user_id.clone(), ScanView::new(&snapshot));
+    }
+
+    Ok(views[&user_id].query(list, &query, filter.as_ref()))
+}
+
+fn load_filter(rules_path: &Path, name: &str) -> Result<Filter, String> {
+    let sets = read_rule_sets(rules_path).map_err(|e| e.to_string())?;
+    sets.iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| format!("No rule set named {:?}", name))?
+        .compile()
+        .map_err(|e| e.to_string())
+}
+
+/// Apply a saved rule set to the last scan of an account
+#[tauri::command]
+async fn filter_relationships(
+    state: State<'_, AppState>,
+    user_id: String,
+    rule_set: String,
+) -> Result<Relationships, String> {
+    let filter = load_filter(&state.rules_path, &rule_set)?;
+    let snapshot = state.latest_snapshot(&user_id).await?;
+    Ok(filter.apply_relationships(&relationships(&snapshot, None)))
+}
+
+/// Stored scans of an account, newest first
+#[tauri::command]
+async fn list_snapshots(state: State<'_, AppState>, user_id: String) -> Result<Vec<SnapshotInfo>, String> {
+    state.store.lock().await.list_snapshots(&user_id).map_err(|e| e.to_string())
+}
+
+/// Follower/following trends across stored scans, optionally only those
+/// taken since a Unix timestamp
+#[tauri::command]
+async fn get_growth_stats(
+    state: State<'_, AppState>,
+    user_id: String,
+    since: Option<u64>,
+) -> Result<GrowthStats, String> {
+    let snapshots = state.store.lock().await.snapshots(&user_id, since).map_err(|e| e.to_string())?;
+    Ok(growth_stats(&snapshots))
+}
+
+/// How long current followers have stayed, and how long those who left did
+#[tauri::command]
+async fn get_follow_durations(state: State<'_, AppState>, user_id: String) -> Result<FollowDurations, String> {
+    let snapshots = state.store.lock().await.snapshots(&user_id, None).map_err(|e| e.to_string())?;
+    Ok(follow_durations(&snapshots))
+}
+
+/// Accounts that unfollowed soon after following, or keep cycling
+#[tauri::command]
+async fn get_quick_unfollowers(
+    state: State<'_, AppState>,
+    user_id: String,
+    options: Option<QuickUnfollowOptions>,
+) -> Result<Vec<FollowCycler>, String> {
+    let snapshots = state.store.lock().await.snapshots(&user_id, None).map_err(|e| e.to_string())?;
+    Ok(quick_unfollowers(&snapshots, &options.unwrap_or_default()))
+}
+
+/// Username and display name changes of a profile, oldest first
+#[tauri::command]
+async fn profile_history(state: State<'_, AppState>, user_id: String) -> Result<Vec<ProfileChange>, String> {
+    state.store.lock().await.profile_history(&user_id).map_err(|e| e.to_string())
+}
+
+/// Unfollow log, newest first (all accounts when `user_id` is omitted)
+#[tauri::command]
+async fn list_actions(
+    state: State<'_, AppState>,
+    user_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ActionRecord>, String> {
+    state
+        .store
+        .lock()
+        .await
+        .actions(user_id.as_deref(), limit.unwrap_or(200))
+        .map_err(|e| e.to_string())
+}
+
+/// List saved rule sets
+#[tauri::command]
+fn list_rule_sets(state: State<'_, AppState>) -> Result<Vec<RuleSet>, String> {
+    read_rule_sets(&state.rules_path).map_err(|e| e.to_string())
+}
+
+/// Validate and save a rule set, replacing any set with the same name
+#[tauri::command]
+fn save_rule_set(state: State<'_, AppState>, rule_set: RuleSet) -> Result<(), String> {
+    rule_set.compile().map_err(|e| e.to_string())?;
+
+    let mut sets = read_rule_sets(&state.rules_path).map_err(|e| e.to_string())?;
+    sets.retain(|s| s.name != rule_set.name);
+    sets.push(rule_set);
+    write_rule_sets(&state.rules_path, &sets).map_err(|e| e.to_string())
+}
+
+/// Delete a saved rule set
+#[tauri::command]
+fn delete_rule_set(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let mut sets = read_rule_sets(&state.rules_path).map_err(|e| e.to_string())?;
+    sets.retain(|s| s.name != name);
+    write_rule_sets(&state.rules_path, &sets).map_err(|e| e.to_string())
 }
 
 /// Fetch followers list
 #[tauri::command]
-async fn fetch_followers(state: State<'_, AppState>, window: tauri::Window, user_id: String) -> Result<Vec<Profile>, String> {
+async fn fetch_followers<R: Runtime>(
+    state: State<'_, AppState>,
+    window: tauri::Window<R>,
+    user_id: String,
+) -> Result<Vec<Profile>, String> {
     let client = state.client.lock().await;
-    client.fetch_followers(&user_id, &window).await.map_err(|e| e.to_string())
+    client.fetch_followers(&user_id, &TauriSink(&window)).await.map_err(|e| e.to_string())
 }
 
 /// Fetch following list
 #[tauri::command]
-async fn fetch_following(state: State<'_, AppState>, window: tauri::Window, user_id: String) -> Result<Vec<Profile>, String> {
+async fn fetch_following<R: Runtime>(
+    state: State<'_, AppState>,
+    window: tauri::Window<R>,
+    user_id: String,
+) -> Result<Vec<Profile>, String> {
     let client = state.client.lock().await;
-    client.fetch_following(&user_id, &window).await.map_err(|e| e.to_string())
+    client.fetch_following(&user_id, &TauriSink(&window)).await.map_err(|e| e.to_string())
+}
+
+/// Stealth meter (0-100): remaining share of the tightest request budget
+#[tauri::command]
+async fn get_integrity(state: State<'_, AppState>) -> Result<u8, String> {
+    let account = state.ledger_account().await;
+    Ok(state.ledger.report(&account).map_err(|e| e.to_string())?.meter)
 }
 
-/// Get current stealth integrity percentage
+/// Requests used and left in every budget window
 #[tauri::command]
-fn get_integrity() -> u8 {
-    get_stealth_integrity()
+async fn get_budget_report(state: State<'_, AppState>) -> Result<BudgetReport, String> {
+    let account = state.ledger_account().await;
+    state.ledger.report(&account).map_err(|e| e.to_string())
+}
+
+/// Whether a scan of `user_id` fits in the read budget, and when it will if not
+#[tauri::command]
+async fn preflight_scan(
+    state: State<'_, AppState>,
+    user_id: String,
+    pacing: Option<String>,
+) -> Result<ScanPreflight, String> {
+    let client = state.client.lock().await;
+    client.preflight_scan(&user_id, pacing.as_deref()).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_budgets(state: State<'_, AppState>) -> Result<BudgetConfig, String> {
+    state.ledger.budgets().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_budgets(state: State<'_, AppState>, budgets: BudgetConfig) -> Result<(), String> {
+    state.ledger.set_budgets(&budgets).map_err(|e| e.to_string())
 }
 
 /// Get logged-in user ID
 #[tauri::command]
 async fn get_logged_user_id(state: State<'_, AppState>) -> Result<Option<String>, String> {
-    let client = state.client.lock().await;
-    Ok(client.user_id.clone())
+    Ok(state.account.read().await.clone())
 }
 
 /// Unfollow a user
 #[tauri::command]
 async fn unfollow_user(state: State<'_, AppState>, user_id: String) -> Result<bool, String> {
-    let client = state.client.lock().await;
-    client.unfollow_user(&user_id).await.map_err(|e| e.to_string())
+    // Unfollow on a clone: the write pacing can wait for minutes
+    let client = state.client.lock().await.clone();
+    let result = client.unfollow_user(&user_id).await.map_err(|e| e.to_string());
+
+    let store = state.store.lock().await;
+    let username = store.profile(&user_id).ok().flatten().map(|p| p.username);
+    if let Err(e) = store.record_action(
+        client.user_id.as_deref(),
+        ActionKind::Unfollow,
+        &user_id,
+        username.as_deref(),
+        result.as_ref().map(|_| ()).map_err(|e| e.as_str()),
+    ) {
+        log::warn!("Could not record unfollow of {}: {}", user_id, e);
+    }
+
+    result
 }
 
 /// Proxy profile picture (returns base64 data URL)
@@ -100,32 +491,298 @@ async fn get_current_user(state: State<'_, AppState>) -> Result<Profile, String>
 
 #[tauri::command]
 async fn restore_session(state: State<'_, AppState>) -> Result<String, String> {
-    state.client.lock().await.restore_session().map_err(|e| e.to_string())
+    let mut client = state.client.lock().await;
+    let result = client.restore_session();
+    *state.account.write().await = client.user_id.clone();
+    result.map_err(|e| e.to_string())
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
+/// Re-read the endpoint override file without restarting
+#[tauri::command]
+async fn reload_endpoints(state: State<'_, AppState>) -> Result<String, String> {
+    let endpoints = if state.endpoints_path.exists() {
+        EndpointConfig::load(&state.endpoints_path).map_err(|e| e.to_string())?
+    } else {
+        EndpointConfig::default()
+    };
+    state.client.lock().await.set_endpoints(endpoints);
+    Ok(format!("Endpoints reloaded ({})", state.endpoints_path.display()))
+}
+
+/// Background scan schedule
+#[tauri::command]
+async fn get_schedule(state: State<'_, AppState>) -> Result<ScheduleConfig, String> {
+    let store = state.store.lock().await;
+    Ok(store.setting(SCHEDULE_SETTING).map_err(|e| e.to_string())?.unwrap_or_default())
+}
+
+#[tauri::command]
+async fn set_schedule(state: State<'_, AppState>, config: ScheduleConfig) -> Result<(), String> {
+    config.validate().map_err(|e| e.to_string())?;
+    state.store.lock().await.set_setting(SCHEDULE_SETTING, &config).map_err(|e| e.to_string())
+}
+
+/// Which changes raise a desktop notification after a scan
+#[tauri::command]
+async fn get_notification_settings(state: State<'_, AppState>) -> Result<NotificationConfig, String> {
+    let store = state.store.lock().await;
+    Ok(store.setting(NOTIFICATIONS_SETTING).map_err(|e| e.to_string())?.unwrap_or_default())
+}
+
+#[tauri::command]
+async fn set_notification_settings(state: State<'_, AppState>, config: NotificationConfig) -> Result<(), String> {
+    state.store.lock().await.set_setting(NOTIFICATIONS_SETTING, &config).map_err(|e| e.to_string())
+}
+
+// ============================================
+// SETTINGS
+// ============================================
+
+#[tauri::command]
+fn get_settings(state: State<'_, AppState>) -> Settings {
+    state.settings.get()
+}
+
+/// Validate, persist and apply new settings, emitting `settings_changed`
+#[tauri::command]
+fn set_settings<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, settings: Settings) -> Result<Settings, String> {
+    settings.save(&state.settings_path).map_err(|e| e.to_string())?;
+    state.settings.set(settings.clone()).map_err(|e| e.to_string())?;
+    set_level(settings.log_level());
+    let _ = app.emit("settings_changed", &settings);
+    Ok(settings)
+}
+
+/// Write the kept diagnostics log, secrets scrubbed, to `path` for a bug
+/// report. Returns the number of lines.
+#[tauri::command]
+fn export_logs(state: State<'_, AppState>, path: String) -> Result<usize, String> {
+    logging::export_logs(&state.log_dir, &path).map_err(|e| e.to_string())
+}
+
+/// Record every request and response, secrets redacted, to `path` (the
+/// log directory by default). Returns the file being written.
+#[tauri::command]
+async fn start_recording(state: State<'_, AppState>, path: Option<String>) -> Result<String, String> {
+    let path = path.map(PathBuf::from).unwrap_or_else(|| state.log_dir.join(RECORDING_FILE));
+    state.client.lock().await.start_recording(&path).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+/// Stop recording, returning the finished file if one was being written
+#[tauri::command]
+async fn stop_recording(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    Ok(state.client.lock().await.stop_recording().map(|p| p.display().to_string()))
+}
+
+/// Built-in and custom pacing profiles a scan can pick
+#[tauri::command]
+fn list_pacing_profiles(state: State<'_, AppState>) -> Vec<PacingProfile> {
+    pacing_profiles(&state.settings.get())
+}
+
+#[tauri::command]
+fn reset_settings<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>) -> Result<Settings, String> {
+    set_settings(app, state, Settings::default())
+}
+
+// ============================================
+// SCHEDULER
+// ============================================
+
+/// Runs due scans of the scheduled accounts for as long as the app is open,
+/// emitting `scheduled_scan` with a summary (or `scheduled_scan_failed`)
+async fn run_scheduler<R: Runtime>(app: AppHandle<R>) {
+    loop {
+        tokio::time::sleep(SCHEDULER_TICK).await;
+        let state = app.state::<AppState>();
+
+        let (config, mut last_runs) = {
+            let store = state.store.lock().await;
+            let config: ScheduleConfig = match store.setting(SCHEDULE_SETTING) {
+                Ok(config) => config.unwrap_or_default(),
+                Err(e) => {
+                    log::warn!("Scheduler: {}", e);
+                    continue;
+                }
+            };
+            let last_runs: HashMap<String, u64> = store.setting(LAST_RUNS_SETTING).ok().flatten().unwrap_or_default();
+            (config, last_runs)
+        };
+        if !config.enabled {
+            continue;
+        }
+
+        for account in &config.accounts {
+            let now = unix_now();
+            if config.next_run(last_runs.get(account).copied(), now) > now {
+                continue;
+            }
+
+            // Recorded before scanning so a failing account isn't retried every tick
+            last_runs.insert(account.clone(), now);
+            if let Err(e) = state.store.lock().await.set_setting(LAST_RUNS_SETTING, &last_runs) {
+                log::warn!("Scheduler: {}", e);
+            }
+
+            log::info!("Scheduled scan of {}", account);
+            match scheduled_scan(&app, &state, account, config.pacing.as_deref()).await {
+                Ok(summary) => {
+                    let _ = app.emit("scheduled_scan", &summary);
+                }
+                Err(e) => {
+                    log::warn!("Scheduled scan of {} failed: {}", account, e);
+                    let _ = app.emit("scheduled_scan_failed", serde_json::json!({ "user_id": account, "error": e }));
+                }
+            }
+        }
+    }
+}
+
+async fn scheduled_scan<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &AppState,
+    user_id: &str,
+    pacing: Option<&str>,
+) -> Result<ScanSummary, ScanError> {
+    // Scan on a clone: an overnight run must not block the UI's commands
+    let client = state.client.lock().await.clone();
+    let snapshot = client.fetch_snapshot(user_id, pacing, &LogSink).await?;
+    let taken_at = snapshot.taken_at;
+    let report = state.store_scan(app, snapshot).await?;
+    Ok(ScanSummary::new(user_id, taken_at, &report))
+}
+
+// ============================================
+// APPLICATION SETUP
+// ============================================
+
+/// Where the app keeps its files
+pub struct AppPaths {
+    /// `settings.json`
+    pub settings: PathBuf,
+    /// Rotating diagnostics log
+    pub log_dir: PathBuf,
+    /// Snapshots, profiles, the action log and the request ledger
+    pub database: PathBuf,
+    /// Saved rule sets
+    pub rules: PathBuf,
+    /// Endpoint overrides
+    pub endpoints: PathBuf,
+    /// Copy of the last loaded cookies.json, unless the settings name one
+    pub session: PathBuf,
+}
+
+impl AppPaths {
+    /// Everything in the platform's app directories, never next to the
+    /// executable, which may be installed read-only. The data directory is
+    /// created here since the store opens in it before anything is saved.
+    pub fn resolve<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Self> {
+        let data_dir = app.path().app_data_dir()?;
+        std::fs::create_dir_all(&data_dir)?;
+        Ok(AppPaths {
+            settings: app.path().app_config_dir()?.join(SETTINGS_FILE),
+            log_dir: app.path().app_log_dir()?,
+            database: data_dir.join(DATABASE_FILE),
+            rules: data_dir.join(RULES_FILE),
+            endpoints: data_dir.join(ENDPOINTS_FILE),
+            session: data_dir.join(SESSION_FILE),
+        })
+    }
+}
+
+/// Plugins and commands, without any state: `run` and the command tests
+/// both build on this
+pub fn app_builder<R: Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
+    builder
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
-        .manage(AppState {
-            client: Mutex::new(GhostClient::new().expect("Failed to create GhostClient")),
-        })
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             load_session,
             restore_session,
+            reload_endpoints,
             warmup_connection,
             get_user_id,
             scan_traitors,
+            scan_relationships,
+            scan_account,
+            query_profiles,
+            filter_relationships,
+            list_snapshots,
+            profile_history,
+            get_growth_stats,
+            get_follow_durations,
+            get_quick_unfollowers,
+            get_schedule,
+            set_schedule,
+            get_notification_settings,
+            set_notification_settings,
+            list_actions,
+            list_rule_sets,
+            save_rule_set,
+            delete_rule_set,
             fetch_followers,
             fetch_following,
             get_integrity,
+            get_budget_report,
+            preflight_scan,
+            get_budgets,
+            set_budgets,
+            get_settings,
+            set_settings,
+            reset_settings,
+            list_pacing_profiles,
+            export_logs,
+            start_recording,
+            stop_recording,
             get_logged_user_id,
             unfollow_user,
             proxy_pic,
             get_current_user
         ])
+}
+
+/// Open the store, build the client and hand the state to `app`. With a
+/// `replay`, the client answers from that recording instead of Instagram.
+pub fn manage_state<R: Runtime>(app: &AppHandle<R>, paths: AppPaths, replay: Option<Arc<Replayer>>) -> anyhow::Result<()> {
+    let settings = SharedSettings::new(Settings::load_or_default(&paths.settings));
+    let ledger = Arc::new(RequestLedger::open(&paths.database)?);
+    let mut client = GhostClient::with_settings(EndpointConfig::load_or_default(&paths.endpoints), settings.clone())?;
+    client.set_ledger(ledger.clone());
+    client.set_default_session_file(paths.session);
+    client.set_replay(replay);
+
+    app.manage(AppState {
+        client: Mutex::new(client),
+        account: RwLock::new(None),
+        store: Mutex::new(Store::open(&paths.database)?),
+        views: Mutex::new(HashMap::new()),
+        ledger,
+        settings,
+        settings_path: paths.settings,
+        log_dir: paths.log_dir,
+        rules_path: paths.rules,
+        endpoints_path: paths.endpoints,
+    });
+    Ok(())
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    app_builder(tauri::Builder::default())
+        .setup(|app| {
+            let paths = AppPaths::resolve(app.handle())?;
+            let log_level = Settings::load_or_default(&paths.settings).log_level();
+            if let Err(e) = init_logging(&paths.log_dir, log_level) {
+                eprintln!("Logging disabled: {}", e);
+            }
+            log::info!("INSTAFollows Ultimate {} starting", app.package_info().version);
+
+            manage_state(app.handle(), paths, None)?;
+            tauri::async_runtime::spawn(run_scheduler(app.handle().clone()));
+            Ok(())
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
-
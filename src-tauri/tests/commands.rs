@@ -0,0 +1,186 @@
+//! Command layer tests: the app runs on Tauri's mock runtime and the client
+//! answers from a recorded Instagram session (`fixtures/instagram.json`)
+
+use instafollows_core::replay::Replayer;
+use instafollows_core::settings::{Settings, SETTINGS_FILE};
+use instafollows_ultimate_lib::{app_builder, manage_state, AppPaths};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime, INVOKE_KEY};
+use tauri::webview::InvokeRequest;
+use tauri::{App, Listener, WebviewWindow, WebviewWindowBuilder};
+
+const PIC_URL: &str =
+    "https://scontent.cdninstagram.com/v/t51.2885-19/4.jpg?stp=dst-jpg_s150x150&_nc_ht=scontent.cdninstagram.com";
+
+struct TestApp {
+    app: App<MockRuntime>,
+    webview: WebviewWindow<MockRuntime>,
+    dir: PathBuf,
+}
+
+impl TestApp {
+    /// A fresh app with its own settings, session copy and store
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("instafollows-commands-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        Settings::default().save(dir.join(SETTINGS_FILE)).unwrap();
+        Self::open(dir)
+    }
+
+    /// Start the app on files an earlier one left behind
+    fn open(dir: PathBuf) -> Self {
+        let app = app_builder(mock_builder()).build(mock_context(noop_assets())).unwrap();
+        let paths = AppPaths {
+            settings: dir.join(SETTINGS_FILE),
+            log_dir: dir.join("logs"),
+            database: dir.join("instafollows.db"),
+            rules: dir.join("rules.json"),
+            endpoints: dir.join("endpoints.json"),
+            session: dir.join("session.json"),
+        };
+        let replayer = Replayer::load(fixture("instagram.json")).unwrap();
+        manage_state(app.handle(), paths, Some(Arc::new(replayer))).unwrap();
+
+        let webview = WebviewWindowBuilder::new(&app, "main", Default::default()).build().unwrap();
+        TestApp { app, webview, dir }
+    }
+
+    fn invoke(&self, cmd: &str, args: Value) -> Result<Value, Value> {
+        get_ipc_response(
+            &self.webview,
+            InvokeRequest {
+                cmd: cmd.into(),
+                callback: CallbackFn(0),
+                error: CallbackFn(1),
+                url: "http://tauri.localhost".parse().unwrap(),
+                body: InvokeBody::Json(args),
+                headers: Default::default(),
+                invoke_key: INVOKE_KEY.to_string(),
+            },
+        )
+        .map(|body| body.deserialize::<Value>().unwrap())
+    }
+
+    fn load_session(&self) -> Result<Value, Value> {
+        self.invoke("load_session", json!({ "path": fixture("cookies.json") }))
+    }
+
+    /// Payloads of every `event` emitted from now on
+    fn collect(&self, event: &str) -> Arc<Mutex<Vec<Value>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        self.app.listen_any(event, move |event| {
+            sink.lock().unwrap().push(serde_json::from_str(event.payload()).unwrap());
+        });
+        events
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+#[test]
+fn load_session_reports_the_account() {
+    let app = TestApp::new("load-session");
+
+    assert_eq!(app.load_session(), Ok(json!("Session loaded. User ID: 42")));
+    assert_eq!(app.invoke("get_logged_user_id", json!({})), Ok(json!("42")));
+    assert!(app.dir.join("session.json").exists());
+}
+
+#[test]
+fn load_session_rejects_a_missing_file() {
+    let app = TestApp::new("load-missing");
+
+    let err = app.invoke("load_session", json!({ "path": app.dir.join("nope.json") })).unwrap_err();
+    assert!(err.as_str().is_some_and(|e| !e.is_empty()), "{}", err);
+}
+
+#[test]
+fn restore_session_reuses_the_saved_copy() {
+    let first = TestApp::new("restore-session");
+    assert_eq!(first.invoke("restore_session", json!({})), Err(json!("No saved session found")));
+    first.load_session().unwrap();
+
+    let second = TestApp::open(first.dir.clone());
+
+    assert_eq!(second.invoke("restore_session", json!({})), Ok(json!("Session loaded. User ID: 42")));
+    assert_eq!(second.invoke("get_logged_user_id", json!({})), Ok(json!("42")));
+}
+
+#[test]
+fn scan_traitors_returns_non_followers_and_emits_progress() {
+    let app = TestApp::new("scan-traitors");
+    app.load_session().unwrap();
+    let progress = app.collect("scan_progress");
+
+    let result = app.invoke("scan_traitors", json!({ "userId": "42" })).unwrap();
+
+    assert_eq!(result["total_followers"], 3);
+    assert_eq!(result["total_following"], 3);
+    let traitors: Vec<&str> = result["traitors"].as_array().unwrap().iter().map(|p| p["id"].as_str().unwrap()).collect();
+    assert_eq!(traitors, ["4"]);
+
+    let progress = progress.lock().unwrap();
+    let pages: Vec<(&str, u64, u64)> = progress
+        .iter()
+        .map(|e| (e["stage"].as_str().unwrap(), e["page"].as_u64().unwrap(), e["current"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(pages, [("followers", 1, 2), ("followers", 2, 3), ("following", 1, 3)]);
+    assert!(progress.iter().all(|e| e["total"] == 3));
+
+    // The scan was stored
+    let snapshots = app.invoke("list_snapshots", json!({ "userId": "42" })).unwrap();
+    assert_eq!(snapshots.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn unfollow_user_needs_a_session() {
+    let app = TestApp::new("unfollow-no-session");
+
+    assert_eq!(app.invoke("unfollow_user", json!({ "userId": "4" })), Err(json!("Session not loaded")));
+}
+
+#[test]
+fn unfollow_user_is_sent_and_logged() {
+    let app = TestApp::new("unfollow");
+    app.load_session().unwrap();
+
+    assert_eq!(app.invoke("unfollow_user", json!({ "userId": "4" })), Ok(json!(true)));
+
+    let actions = app.invoke("list_actions", json!({ "userId": "42" })).unwrap();
+    let actions = actions.as_array().unwrap();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0]["target_id"], "4");
+}
+
+#[test]
+fn proxy_pic_returns_a_data_url() {
+    let app = TestApp::new("proxy-pic");
+
+    assert_eq!(
+        app.invoke("proxy_pic", json!({ "url": PIC_URL })),
+        Ok(json!("data:image/jpeg;base64,/9j/4AAQSkZJRgAB/9k="))
+    );
+}
+
+#[test]
+fn proxy_pic_fails_for_an_unknown_picture() {
+    let app = TestApp::new("proxy-unknown");
+
+    let err = app.invoke("proxy_pic", json!({ "url": "https://scontent.cdninstagram.com/v/t51.2885-19/9.jpg" })).unwrap_err();
+    assert!(err.as_str().unwrap().contains("No recorded response left"), "{}", err);
+}
@@ -233,7 +233,8 @@ function App() {
       const newIntegrity = await invoke<number>("get_integrity");
       setIntegrity(newIntegrity);
     } catch (e) {
-      setError(String(e));
+      // Scan commands reject with { kind, message, ... }
+      setError(typeof e === "object" && e !== null && "message" in e ? String(e.message) : String(e));
       setStatus("READY");
     } finally {
       setIsLoading(false);